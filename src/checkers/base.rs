@@ -1,8 +1,12 @@
 use core::fmt::Debug as DebugTrait;
 use std::io;
+use std::path::PathBuf;
 use thiserror::Error;
 
-use crate::{checkers::RelativeUrl, uri::PathError};
+use crate::{
+    checkers::{RelativeUrl, patch::Patch, report::CheckReport},
+    uri::PathError,
+};
 
 use super::GenericChecker;
 
@@ -12,6 +16,7 @@ pub enum CheckResult {
     FixNeeded(String),
     FixExecuted(String),
     Error(String),
+    Skipped(String),
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -20,6 +25,10 @@ pub(crate) enum CheckDefinitionError {
     InvalidDefinition(String),
     #[error("Unknown checktype ({0})")]
     UnknownCheckType(String),
+    #[error("file with checks can not be read")]
+    FileCanNotBeRead,
+    #[error("invalid toml ({0})")]
+    InvalidToml(String),
 }
 
 #[derive(Error, Debug)]
@@ -40,7 +49,7 @@ pub(crate) enum CheckError {
     PermissionsNotAccessable,
     #[error("git error ({0})")]
     GitError(String),
-    #[error("file can not be fetched")]
+    #[error("file can not be fetched ({0})")]
     FetchError(#[from] PathError),
     #[error("{0}")]
     String(String),
@@ -50,6 +59,7 @@ pub(crate) enum CheckError {
 
 pub(crate) trait CheckConstructor {
     type Output;
+    const KEYS: &'static [&'static str];
     fn from_check_table(
         generic_check: GenericChecker,
         value: toml_edit::Table,
@@ -76,6 +86,7 @@ pub(crate) trait Checker: DebugTrait {
             CheckResult::FixNeeded(action) => ("❌", format!(" - {action}")),
             CheckResult::FixExecuted(action) => ("🔧", format!(" - {action}")),
             CheckResult::Error(e) => ("🚨", format!(" - {e}")),
+            CheckResult::Skipped(reason) => ("⏭️", format!(" - skipped, {reason}")),
         };
         let msg = format!(
             "{} {} - {} - {}{}",
@@ -90,15 +101,48 @@ pub(crate) trait Checker: DebugTrait {
             CheckResult::FixExecuted(_) => log::info!("{msg}"),
             CheckResult::FixNeeded(_) => log::warn!("{msg}"),
             CheckResult::Error(_) => log::error!("{msg}"),
+            CheckResult::Skipped(_) => log::warn!("{msg}"),
+        }
+    }
+
+    fn report(&self, check_result: CheckResult) -> CheckReport {
+        CheckReport {
+            checker_type: self.checker_type(),
+            checker_object: self.checker_object(),
+            file_with_checks: self.generic_checker().file_with_checks.short_url_str(),
+            tags: self.generic_checker().tags.clone(),
+            result: check_result,
+            project: None,
         }
     }
 
     fn check_(&self, fix: bool) -> Result<CheckResult, CheckError>;
 
+    fn patch(&self, _patch: &mut Patch) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![]
+    }
+
+    fn skipped_by_condition(&self) -> Option<CheckResult> {
+        let generic_checker = self.generic_checker();
+        match &generic_checker.when {
+            Some(when) if !when.evaluate(&generic_checker.variables) => Some(CheckResult::Skipped(
+                format!("condition `{}` is not met", when.expression),
+            )),
+            _ => None,
+        }
+    }
+
     fn check(&self, fix: bool) -> CheckResult {
-        let check_result = match self.check_(fix) {
-            Ok(check_result) => check_result,
-            Err(e) => CheckResult::Error(e.to_string()),
+        let check_result = match self.skipped_by_condition() {
+            Some(check_result) => check_result,
+            None => match self.check_(fix) {
+                Ok(check_result) => check_result,
+                Err(e) => CheckResult::Error(e.to_string()),
+            },
         };
 
         self.print(&check_result);
@@ -0,0 +1,117 @@
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{checkers::base::CheckDefinitionError, uri::PathError};
+
+pub(crate) const CHECKSUM_KEYS: &[&str] = &["sha256", "sha512"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Checksum {
+    Sha256(String),
+    Sha512(String),
+}
+
+impl Checksum {
+    pub(crate) fn from_table(
+        table: &dyn toml_edit::TableLike,
+    ) -> Result<Vec<Checksum>, CheckDefinitionError> {
+        let mut checksums = vec![];
+        for key in CHECKSUM_KEYS {
+            let Some(item) = table.get(key) else {
+                continue;
+            };
+            let length = match *key {
+                "sha256" => 64,
+                _ => 128,
+            };
+            let digest = match item.as_str() {
+                Some(digest)
+                    if digest.len() == length && digest.chars().all(|c| c.is_ascii_hexdigit()) =>
+                {
+                    digest.to_lowercase()
+                }
+                _ => {
+                    return Err(CheckDefinitionError::InvalidDefinition(format!(
+                        "{key} is not a hex string of {length} characters"
+                    )));
+                }
+            };
+            checksums.push(match *key {
+                "sha256" => Checksum::Sha256(digest),
+                _ => Checksum::Sha512(digest),
+            });
+        }
+        Ok(checksums)
+    }
+
+    fn algorithm(&self) -> &'static str {
+        match self {
+            Checksum::Sha256(_) => "sha256",
+            Checksum::Sha512(_) => "sha512",
+        }
+    }
+
+    fn digest(&self, contents: &[u8]) -> String {
+        match self {
+            Checksum::Sha256(_) => format!("{:x}", Sha256::digest(contents)),
+            Checksum::Sha512(_) => format!("{:x}", Sha512::digest(contents)),
+        }
+    }
+}
+
+pub(crate) fn verify_checksums(
+    checksums: &[Checksum],
+    source: &str,
+    contents: &[u8],
+) -> Result<(), PathError> {
+    for checksum in checksums {
+        let (Checksum::Sha256(expected) | Checksum::Sha512(expected)) = checksum;
+        let actual = checksum.digest(contents);
+        if actual != *expected {
+            return Err(PathError::ChecksumMismatch {
+                path: source.to_string(),
+                algorithm: checksum.algorithm(),
+                expected: expected.clone(),
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256_OF_A: &str = "ca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb";
+
+    #[test]
+    fn test_verify_checksums() {
+        let mut table = toml_edit::Table::new();
+        table.insert("sha256", SHA256_OF_A.to_uppercase().into());
+        let checksums = Checksum::from_table(&table).unwrap();
+
+        assert!(verify_checksums(&checksums, "file", b"a").is_ok());
+        assert_eq!(
+            verify_checksums(&checksums, "file", b"b")
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "sha256 of file is 3e23e8160039594a33894f6564e1b1348bbd7a0088d42c4acb73eeaed59c009d, \
+                 but {SHA256_OF_A} is expected"
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_checksum() {
+        let mut table = toml_edit::Table::new();
+        table.insert("sha512", SHA256_OF_A.into());
+
+        assert_eq!(
+            Checksum::from_table(&table),
+            Err(CheckDefinitionError::InvalidDefinition(
+                "sha512 is not a hex string of 128 characters".into()
+            ))
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{HashMap, HashSet, hash_map::Entry};
+
+use crate::checkers::{
+    CheckDefinitions,
+    base::{CheckDefinitionError, CheckResult, Checker},
+};
+
+fn add_issue(check_definitions: &mut CheckDefinitions, check: &dyn Checker, message: String) {
+    let generic_checker = check.generic_checker();
+    check_definitions.add_issue(
+        &generic_checker.file_with_checks,
+        generic_checker.location,
+        CheckDefinitionError::InvalidDefinition(message),
+    );
+}
+
+fn find_path(
+    from: usize,
+    to: usize,
+    dependencies: &[Vec<usize>],
+    visited: &mut HashSet<usize>,
+) -> Option<Vec<usize>> {
+    for &dependency in dependencies[from].iter() {
+        if dependency == to {
+            return Some(vec![to]);
+        }
+        if visited.insert(dependency)
+            && let Some(mut path) = find_path(dependency, to, dependencies, visited)
+        {
+            path.insert(0, dependency);
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Checkers with an id which is already used, with an unknown dependency or in a dependency
+/// cycle are removed and reported as issues.
+pub(crate) fn order_by_dependencies(check_definitions: &mut CheckDefinitions) {
+    let mut checks = std::mem::take(&mut check_definitions.checks)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    let check = |checks: &Vec<Option<Box<dyn Checker>>>, index: usize| {
+        checks[index]
+            .as_ref()
+            .expect("checker is not ordered yet")
+            .generic_checker()
+            .clone()
+    };
+
+    let mut removed = vec![false; checks.len()];
+    let mut indexes: HashMap<String, usize> = HashMap::new();
+    for index in 0..checks.len() {
+        let Some(id) = check(&checks, index).id else {
+            continue;
+        };
+        match indexes.entry(id) {
+            Entry::Occupied(entry) => {
+                add_issue(
+                    check_definitions,
+                    checks[index].as_deref().unwrap(),
+                    format!("id `{}` is used by more than one check", entry.key()),
+                );
+                removed[index] = true;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+            }
+        }
+    }
+
+    let mut dependencies: Vec<Vec<usize>> = vec![vec![]; checks.len()];
+    for index in 0..checks.len() {
+        if removed[index] {
+            continue;
+        }
+        for id in check(&checks, index).depends_on {
+            match indexes.get(&id) {
+                Some(dependency) => dependencies[index].push(*dependency),
+                None => {
+                    add_issue(
+                        check_definitions,
+                        checks[index].as_deref().unwrap(),
+                        format!("depends_on contains the unknown id `{id}`"),
+                    );
+                    removed[index] = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    // take the first checker of which all dependencies are ordered, until none is left
+    let mut ordered = vec![false; checks.len()];
+    while let Some(index) = (0..checks.len()).find(|&index| {
+        !removed[index]
+            && !ordered[index]
+            && dependencies[index]
+                .iter()
+                .all(|&dependency| ordered[dependency])
+    }) {
+        ordered[index] = true;
+        check_definitions
+            .checks
+            .push(checks[index].take().expect("checker is ordered once"));
+    }
+
+    for index in 0..checks.len() {
+        if removed[index] || ordered[index] {
+            continue;
+        }
+        let id = |index: usize| check(&checks, index).id.unwrap_or_default();
+        let message = match find_path(index, index, &dependencies, &mut HashSet::new()) {
+            // the cycle is reported once, at its first checker
+            Some(cycle) if cycle.iter().all(|&other| other >= index) => format!(
+                "depends_on contains a dependency cycle: {} -> {}",
+                id(index),
+                cycle
+                    .iter()
+                    .map(|&other| id(other))
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ),
+            Some(_) => continue,
+            None => {
+                let dependency = dependencies[index]
+                    .iter()
+                    .find(|&&dependency| !ordered[dependency])
+                    .expect("a checker which is not ordered has a dependency which is not");
+                format!(
+                    "depends_on contains `{}` which can not be executed",
+                    id(*dependency)
+                )
+            }
+        };
+        add_issue(
+            check_definitions,
+            checks[index].as_deref().unwrap(),
+            message,
+        );
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct FailedDependencies {
+    // the ids of the checkers, with whether the checker is skipped instead of failed
+    ids: HashMap<String, bool>,
+}
+
+impl FailedDependencies {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn skipped_result(&self, check: &dyn Checker) -> Option<CheckResult> {
+        check
+            .generic_checker()
+            .depends_on
+            .iter()
+            .find_map(|id| Some((id, *self.ids.get(id)?)))
+            .map(|(id, skipped)| match skipped {
+                true => CheckResult::Skipped(format!("dependency `{id}` is skipped")),
+                false => CheckResult::Skipped(format!("dependency `{id}` failed")),
+            })
+    }
+
+    /// A skip skips the dependents as well, as the skipped checker did not make sure they can be
+    /// executed.
+    pub(crate) fn record(&mut self, check: &dyn Checker, result: &CheckResult) {
+        if let Some(id) = &check.generic_checker().id {
+            match result {
+                CheckResult::Error(_) => self.ids.insert(id.clone(), false),
+                CheckResult::Skipped(_) => self.ids.insert(id.clone(), true),
+                _ => None,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{checkers::load_checks_from_path, cli::run_checks, uri::ReadablePath};
+
+    use super::*;
+
+    fn load_checks(dir: &std::path::Path, checks_toml: &str) -> CheckDefinitions {
+        let path_with_checkers = dir.join("check-config.toml");
+        std::fs::write(&path_with_checkers, checks_toml).unwrap();
+        let path_with_checkers = ReadablePath::from_string(
+            &format!("file://{}", path_with_checkers.to_str().unwrap()),
+            None,
+        )
+        .unwrap();
+        load_checks_from_path(&path_with_checkers, &mut HashMap::new())
+    }
+
+    fn objects(check_definitions: &CheckDefinitions) -> Vec<String> {
+        check_definitions
+            .checks
+            .iter()
+            .map(|check| check.checker_object())
+            .collect()
+    }
+
+    #[test]
+    fn test_order_by_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let check_definitions = load_checks(
+            dir.path(),
+            r#"
+[[file_present]]
+file = "/tmp/1"
+depends_on = ["lines"]
+
+[[lines_present]]
+id = "lines"
+file = "/tmp/2"
+lines = "a"
+depends_on = ["absent"]
+
+[[file_present]]
+file = "/tmp/3"
+
+[file_absent]
+id = "absent"
+file = "/tmp/4"
+"#,
+        );
+
+        assert!(check_definitions.issues.is_empty());
+        assert_eq!(
+            objects(&check_definitions),
+            vec!["/tmp/3", "/tmp/4", "/tmp/2", "/tmp/1"]
+        );
+    }
+
+    #[test]
+    fn test_order_by_dependencies_with_issues() {
+        let dir = tempfile::tempdir().unwrap();
+        let check_definitions = load_checks(
+            dir.path(),
+            r#"
+[[file_present]]
+id = "a"
+file = "/tmp/1"
+depends_on = ["b"]
+
+[[file_present]]
+id = "b"
+file = "/tmp/2"
+depends_on = ["a"]
+
+[[file_present]]
+file = "/tmp/3"
+depends_on = ["b"]
+
+[[file_present]]
+id = "a"
+file = "/tmp/4"
+
+[[file_present]]
+file = "/tmp/5"
+depends_on = ["unknown"]
+
+[[file_present]]
+file = "/tmp/6"
+"#,
+        );
+
+        assert_eq!(objects(&check_definitions), vec!["/tmp/6"]);
+        assert_eq!(
+            check_definitions
+                .issues
+                .iter()
+                .map(|issue| (issue.location, issue.error.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Some((16, 1)),
+                    "invalid check definition (id `a` is used by more than one check)".into()
+                ),
+                (
+                    Some((20, 1)),
+                    "invalid check definition (depends_on contains the unknown id `unknown`)"
+                        .into()
+                ),
+                (
+                    Some((2, 1)),
+                    "invalid check definition (depends_on contains a dependency cycle: a -> b -> a)"
+                        .into()
+                ),
+                (
+                    Some((12, 1)),
+                    "invalid check definition (depends_on contains `b` which can not be executed)"
+                        .into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependents_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        // a directory where a file is expected, so its lines can not be read
+        std::fs::create_dir(dir.path().join("dir")).unwrap();
+        let check_definitions = load_checks(
+            dir.path(),
+            &format!(
+                r#"
+[[lines_present]]
+id = "fails"
+file = "{dir}/dir"
+lines = "a"
+
+[[file_present]]
+id = "skipped"
+file = "{dir}/other_file"
+depends_on = ["fails"]
+
+[[file_present]]
+file = "{dir}/last_file"
+depends_on = ["skipped"]
+
+[[file_present]]
+file = "{dir}/independent_file"
+
+[[file_present]]
+id = "conditional"
+file = "{dir}/conditional_file"
+when = "false"
+
+[[file_present]]
+file = "{dir}/dependent_file"
+depends_on = ["conditional"]
+"#,
+                dir = dir.path().to_string_lossy()
+            ),
+        );
+
+        let run_report = run_checks(&check_definitions.checks, true, &mut None);
+
+        let results = run_report
+            .checks
+            .iter()
+            .map(|check| check.result.clone())
+            .collect::<Vec<_>>();
+        assert!(matches!(results[0], CheckResult::Error(_)));
+        assert_eq!(
+            results[1..],
+            [
+                CheckResult::Skipped("dependency `fails` failed".into()),
+                CheckResult::Skipped("dependency `skipped` is skipped".into()),
+                CheckResult::FixExecuted("create file".into()),
+                CheckResult::Skipped("condition `false` is not met".into()),
+                CheckResult::Skipped("dependency `conditional` is skipped".into()),
+            ]
+        );
+        assert!(!dir.path().join("other_file").exists());
+        assert!(dir.path().join("independent_file").exists());
+    }
+}
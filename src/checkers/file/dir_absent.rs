@@ -1,7 +1,8 @@
 use std::fs;
+use std::path::PathBuf;
 
 use crate::{
-    checkers::{base::CheckResult, file::get_string_value_from_checktable},
+    checkers::{base::CheckResult, file::get_string_value_from_checktable, patch::Patch},
     uri::WritablePath,
 };
 
@@ -21,6 +22,7 @@ pub(crate) struct DirAbsent {
 
 impl CheckConstructor for DirAbsent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["dir"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -45,6 +47,10 @@ impl Checker for DirAbsent {
     fn checker_object(&self) -> String {
         self.dir.to_string()
     }
+
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.dir.as_ref().clone()]
+    }
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         let mut action_messages: Vec<String> = vec![];
 
@@ -67,12 +73,16 @@ impl Checker for DirAbsent {
 
         Ok(check_result)
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        patch.remove_dir(self.dir.as_ref())
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::checkers::{base::CheckResult, test_helpers};
+    use crate::checkers::{base::CheckResult, patch::FileChange, test_helpers};
 
     use super::*;
 
@@ -121,4 +131,25 @@ mod tests {
             CheckResult::NoFixNeeded
         );
     }
+
+    #[test]
+    fn test_dir_absent_patch() {
+        let (dir_absent_check, _tempdir) = get_dir_absent_check();
+        let file = dir_absent_check.dir.as_ref().join("file");
+        fs::create_dir(dir_absent_check.dir.as_ref()).unwrap();
+        fs::write(&file, "contents").unwrap();
+
+        let mut patch = Patch::new();
+        dir_absent_check.patch(&mut patch).unwrap();
+
+        assert_eq!(
+            patch.changed_files(),
+            vec![&FileChange {
+                path: file.clone(),
+                old_contents: Some("contents".into()),
+                new_contents: None,
+            }]
+        );
+        assert!(file.exists());
+    }
 }
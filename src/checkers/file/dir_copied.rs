@@ -1,6 +1,8 @@
+use std::path::PathBuf;
+
 use crate::{
-    checkers::{base::CheckResult, file::get_string_value_from_checktable},
-    uri::WritablePath,
+    checkers::{base::CheckResult, file::get_string_value_from_checktable, patch::Patch},
+    uri::{WritablePath, copy_atomically},
 };
 
 use super::super::{
@@ -25,6 +27,7 @@ pub(crate) struct DirCopied {
 // which contain the file which defines this check.
 impl CheckConstructor for DirCopied {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["source", "destination", "destination_dir"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -86,8 +89,33 @@ fn copy_dir_contents(src: &std::path::Path, dst: &std::path::Path) -> std::io::R
             // Recurse into subdirectory
             copy_dir_contents(&path, &dest_path)?;
         } else {
-            // Copy file
-            std::fs::copy(&path, &dest_path)?;
+            copy_atomically(&path, &dest_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn patch_dir_contents(
+    src: &std::path::Path,
+    dst: &std::path::Path,
+    patch: &mut Patch,
+) -> Result<(), CheckError> {
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let dest_path = dst.join(entry.file_name());
+
+        if path.is_dir() {
+            patch_dir_contents(&path, &dest_path, patch)?;
+        } else {
+            let contents = std::fs::read_to_string(&path).map_err(|_| {
+                CheckError::String(format!(
+                    "binary file {} can not be added to a patch",
+                    path.to_string_lossy()
+                ))
+            })?;
+            patch.set_contents(&dest_path, Some(contents))?;
         }
     }
 
@@ -105,6 +133,10 @@ impl Checker for DirCopied {
     fn checker_object(&self) -> String {
         self.source.as_ref().to_string_lossy().to_string()
     }
+
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.destination.as_ref().clone()]
+    }
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         // TODO: check whether the file is changed
         let mut action_messages: Vec<String> = vec![];
@@ -134,6 +166,16 @@ impl Checker for DirCopied {
 
         Ok(check_result)
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        if !self.source.exists() {
+            return Err(CheckError::String("source dir does not exists".into()));
+        }
+        if self.destination.exists() {
+            return Ok(());
+        }
+        patch_dir_contents(self.source.as_ref(), self.destination.as_ref(), patch)
+    }
 }
 
 #[cfg(test)]
@@ -194,4 +236,36 @@ mod tests {
                 .exists()
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_dir_contents_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source");
+        let destination = dir.path().join("destination");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&destination).unwrap();
+        std::fs::write(source.join("script"), "new").unwrap();
+        std::fs::set_permissions(source.join("script"), PermissionsExt::from_mode(0o755)).unwrap();
+        std::fs::write(source.join("config"), "new").unwrap();
+        std::fs::write(destination.join("config"), "old").unwrap();
+        std::fs::set_permissions(destination.join("config"), PermissionsExt::from_mode(0o600))
+            .unwrap();
+
+        copy_dir_contents(&source, &destination).unwrap();
+
+        let mode = |path: PathBuf| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(
+            std::fs::read_to_string(destination.join("config")).unwrap(),
+            "new"
+        );
+        // like a new file, an existing file gets the mode of the source
+        assert_eq!(
+            mode(destination.join("config")),
+            mode(source.join("config"))
+        );
+        assert_eq!(mode(destination.join("script")), 0o755);
+    }
 }
@@ -1,6 +1,7 @@
 use std::fs;
 #[cfg(not(target_os = "windows"))]
 use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
 
 use crate::{
     checkers::{
@@ -28,6 +29,7 @@ pub(crate) struct DirPresent {
 
 impl CheckConstructor for DirPresent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["dir", "permissions"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -58,6 +60,10 @@ impl Checker for DirPresent {
     fn checker_object(&self) -> String {
         self.dir.to_string()
     }
+
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.dir.as_ref().clone()]
+    }
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         let mut action_messages: Vec<String> = vec![];
 
@@ -1,4 +1,9 @@
-use crate::{checkers::file::FileCheck, mapping::generic::Mapping};
+use std::path::PathBuf;
+
+use crate::{
+    checkers::{file::FileCheck, patch::Patch},
+    mapping::generic::Mapping,
+};
 
 use super::super::{
     GenericChecker,
@@ -16,6 +21,8 @@ pub(crate) struct EntryAbsent {
 // entry.key = ["item1"]
 impl CheckConstructor for EntryAbsent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["file", "file_type", "indent", "entry"];
+
     fn from_check_table(
         generic_check: GenericChecker,
         check_table: toml_edit::Table,
@@ -59,6 +66,10 @@ impl Checker for EntryAbsent {
         self.file_check.check_object()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
+
     fn generic_checker(&self) -> &GenericChecker {
         &self.file_check.generic_check
     }
@@ -70,6 +81,11 @@ impl Checker for EntryAbsent {
 
         self.file_check.conclude_check_with_new_doc(doc, fix)
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.file_check
+            .patch_with_new_doc(patch, |doc| remove_entries(doc, &self.absent))
+    }
 }
 
 fn remove_entries(doc: &mut dyn Mapping, entries_to_remove: &toml_edit::Table) {
@@ -1,4 +1,6 @@
-use crate::checkers::file::FileCheck;
+use std::path::PathBuf;
+
+use crate::checkers::{file::FileCheck, patch::Patch};
 pub(crate) use crate::mapping::generic::Mapping;
 
 use super::super::{
@@ -17,6 +19,8 @@ pub(crate) struct EntryPresent {
 // entry.key = ["item1"]
 impl CheckConstructor for EntryPresent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["file", "file_type", "indent", "entry"];
+
     fn from_check_table(
         generic_check: GenericChecker,
         check_table: toml_edit::Table,
@@ -60,6 +64,10 @@ impl Checker for EntryPresent {
         self.file_check.check_object()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
+
     fn generic_checker(&self) -> &GenericChecker {
         &self.file_check.generic_check
     }
@@ -71,6 +79,11 @@ impl Checker for EntryPresent {
 
         self.file_check.conclude_check_with_new_doc(doc, fix)
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.file_check
+            .patch_with_new_doc(patch, |doc| add_entries(doc, &self.present))
+    }
 }
 
 fn add_entries(doc: &mut dyn Mapping, entries_to_add: &toml_edit::Table) {
@@ -1,5 +1,8 @@
+use std::path::PathBuf;
+
 use crate::checkers::base::CheckResult;
 use crate::checkers::file::FileCheck;
+use crate::checkers::patch::Patch;
 
 use super::super::GenericChecker;
 use super::super::base::{CheckConstructor, CheckDefinitionError, CheckError, Checker};
@@ -13,6 +16,7 @@ pub(crate) struct FileAbsent {
 // file = "file"
 impl CheckConstructor for FileAbsent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["file", "file_type", "indent"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -31,6 +35,10 @@ impl Checker for FileAbsent {
         self.file_check.check_object()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
+
     fn generic_checker(&self) -> &GenericChecker {
         &self.file_check.generic_check
     }
@@ -38,6 +46,10 @@ impl Checker for FileAbsent {
     fn check_(&self, fix: bool) -> Result<CheckResult, CheckError> {
         self.file_check.conclude_check_with_remove(fix)
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.file_check.patch_remove(patch)
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,15 @@
+use std::path::PathBuf;
+
 use similar::TextDiff;
 
 use crate::{
-    checkers::{base::CheckResult, file::get_string_value_from_checktable, utils::replace_vars},
+    checkers::{
+        base::CheckResult,
+        checksum::{Checksum, verify_checksums},
+        file::get_string_value_from_checktable,
+        patch::Patch,
+        template::render,
+    },
     uri::{ReadPath, ReadablePath, WritablePath},
 };
 
@@ -16,20 +24,31 @@ pub(crate) struct FileCopied {
     destination: WritablePath,
     generic_check: GenericChecker,
     is_template: bool,
-    // TODO: add digest to validate the download
+    // expected checksums of the source
+    checksums: Vec<Checksum>,
 }
 
 //[[file_copied]]
 // source = "path or url of file to copy"
 // destination = "path (including filename) to copy to"
 // destination_dir = "path (excluding filename) to copy to"
-// is_template = true # optional
+// is_template = true # optional, render source as template
+// sha256 = "hex digest of the source" # optional
+// sha512 = "hex digest of the source" # optional
 //
 // check if file is copied
 // if source is a relative path, it's relative to the check file, so the dir
 // which contain the file which defines this check.
 impl CheckConstructor for FileCopied {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &[
+        "source",
+        "destination",
+        "destination_dir",
+        "is_template",
+        "sha256",
+        "sha512",
+    ];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -69,11 +88,14 @@ impl CheckConstructor for FileCopied {
             None => false,
         };
 
+        let checksums = Checksum::from_table(&check_table)?;
+
         Ok(Self {
             destination,
             source,
             generic_check,
             is_template,
+            checksums,
         })
     }
 }
@@ -89,12 +111,17 @@ impl Checker for FileCopied {
         self.source.as_ref().to_string()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.destination.as_ref().clone()]
+    }
+
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         match self.source.exists() {
             Ok(false) => return Err(CheckError::String("source file does not exists".into())),
             Ok(true) => (),
             Err(e) => return Err(CheckError::String(e.to_string())),
         }
+        self.verify_source()?;
 
         if self.source.is_utf8()? {
             self.check_utf8(fix)
@@ -102,8 +129,50 @@ impl Checker for FileCopied {
             self.check_binary(fix)
         }
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.verify_source()?;
+        if !self.source.is_utf8()? {
+            if self.check_binary(false)? == CheckResult::NoFixNeeded {
+                return Ok(());
+            }
+            return Err(CheckError::String(
+                "binary file can not be added to a patch".into(),
+            ));
+        }
+
+        let new_contents = self.new_contents()?;
+        if patch.contents(self.destination.as_ref())?.as_ref() != Some(&new_contents) {
+            patch.set_contents(self.destination.as_ref(), Some(new_contents))?;
+        }
+        Ok(())
+    }
 }
 impl FileCopied {
+    fn verify_source(&self) -> Result<(), CheckError> {
+        if !self.checksums.is_empty() {
+            verify_checksums(
+                &self.checksums,
+                self.source.as_ref().as_str(),
+                &self.source.read_to_bytes()?,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn new_contents(&self) -> Result<String, CheckError> {
+        let new_contents = self.source.read_to_string()?;
+        if self.is_template {
+            return render(
+                new_contents.as_str(),
+                &self.generic_check.variables,
+                &self.generic_check.file_with_checks,
+            )
+            .map_err(|e| CheckError::String(format!("template can not be rendered ({e})")));
+        }
+        Ok(new_contents)
+    }
+
     fn check_utf8(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         let destination_exists = self.destination.exists();
         let mut action_messages: Vec<String> = vec![];
@@ -114,10 +183,7 @@ impl FileCopied {
             "".to_string()
         };
 
-        let mut new_contents = self.source.read_to_string()?;
-        if self.is_template {
-            new_contents = replace_vars(new_contents.as_str(), &self.generic_check.variables)
-        }
+        let new_contents = self.new_contents()?;
 
         let source_and_destination_are_different =
             destination_exists && old_contents != new_contents;
@@ -197,7 +263,10 @@ mod tests {
 
     use std::fs::write;
 
-    use crate::checkers::{base::CheckResult, test_helpers};
+    use crate::{
+        checkers::{base::CheckResult, test_helpers},
+        uri::PathError,
+    };
 
     use super::*;
 
@@ -291,4 +360,40 @@ mod tests {
             "blabla"
         );
     }
+
+    #[test]
+    fn test_file_copied_with_checksum() {
+        use crate::http::tests::{response, serve};
+
+        let (url, _server) = serve(vec![response("200 OK", "", "contents")]);
+        let dir = tempdir().unwrap();
+        let destination = dir.path().join("destination");
+        let file_copied_check = |sha256: &str| {
+            let mut check_table = toml_edit::Table::new();
+            check_table.insert("source", url.to_string().into());
+            check_table.insert(
+                "destination",
+                destination.to_string_lossy().to_string().into(),
+            );
+            check_table.insert("sha256", sha256.into());
+            FileCopied::from_check_table(test_helpers::get_generic_check(), check_table).unwrap()
+        };
+
+        let error = file_copied_check(&"0".repeat(64)).check_(true).unwrap_err();
+        assert!(matches!(
+            error,
+            CheckError::FetchError(PathError::ChecksumMismatch { .. })
+        ));
+        assert!(!destination.exists());
+
+        assert_eq!(
+            file_copied_check("d1b2a59fbea7e20077af9f91b27e95e865061b270be03ff539ab3b73587882e8")
+                .check_(true)
+                .unwrap(),
+            CheckResult::FixExecuted(
+                "copy file\nSet file contents to: \n@@ -0,0 +1 @@\n+contents\n\\ No newline at end of file\n"
+                    .into()
+            )
+        );
+    }
 }
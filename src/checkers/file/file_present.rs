@@ -1,10 +1,14 @@
 use std::fs::Permissions;
 #[cfg(not(target_os = "windows"))]
 use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
 
 use regex::Regex;
 
-use crate::checkers::file::{FileCheck, get_option_string_value_from_checktable};
+use crate::checkers::{
+    file::{FileCheck, get_option_string_value_from_checktable},
+    patch::Patch,
+};
 
 use super::super::{
     GenericChecker,
@@ -74,6 +78,14 @@ pub(crate) fn get_regex_from_checktable(
 
 impl CheckConstructor for FilePresent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &[
+        "file",
+        "file_type",
+        "indent",
+        "permissions",
+        "placeholder",
+        "regex",
+    ];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -105,6 +117,10 @@ impl Checker for FilePresent {
     fn checker_object(&self) -> String {
         self.file_check.check_object()
     }
+
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         self.file_check.conclude_check_file_exists(
             self.placeholder.clone(),
@@ -113,6 +129,11 @@ impl Checker for FilePresent {
             fix,
         )
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.file_check
+            .patch_file_exists(patch, self.placeholder.clone())
+    }
 }
 
 #[cfg(test)]
@@ -266,4 +287,41 @@ mod tests {
             CheckResult::NoFixNeeded
         );
     }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_file_present_patch_is_the_fix() {
+        let (file_present_check, _tempdir) = get_file_present_check(
+            Some("placeholder".into()),
+            Some("600".into()),
+            Some("[0-9]{1,3}".into()),
+        );
+        let file_to_check = file_present_check.file_check.file_to_check.as_ref().clone();
+        let patched_contents = |check: &FilePresent| {
+            let mut patch = Patch::new();
+            check.patch(&mut patch).unwrap();
+            patch
+                .changed_files()
+                .first()
+                .map(|change| change.new_contents.clone())
+        };
+
+        assert_eq!(
+            patched_contents(&file_present_check),
+            Some(Some("placeholder".into()))
+        );
+
+        // the contents of an existing file which does not match the regex are kept by the fix
+        write(&file_to_check, "bla").unwrap();
+        assert_eq!(patched_contents(&file_present_check), None);
+        file_present_check.check_(true).unwrap();
+        assert_eq!(std::fs::read_to_string(&file_to_check).unwrap(), "bla");
+
+        // the contents of a file which only needs other permissions are kept
+        write(&file_to_check, "129").unwrap();
+        std::fs::set_permissions(&file_to_check, PermissionsExt::from_mode(0o644)).unwrap();
+        assert_eq!(patched_contents(&file_present_check), None);
+        file_present_check.check_(true).unwrap();
+        assert_eq!(std::fs::read_to_string(&file_to_check).unwrap(), "129");
+    }
 }
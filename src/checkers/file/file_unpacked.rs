@@ -1,11 +1,14 @@
 use std::fs::File;
+use std::path::PathBuf;
 
 use crate::{
     checkers::{
         base::CheckResult,
+        checksum::{Checksum, verify_checksums},
         file::{get_option_string_value_from_checktable, get_string_value_from_checktable},
+        patch::Patch,
     },
-    uri::WritablePath,
+    uri::{ReadPath, WritablePath},
 };
 
 use super::super::{
@@ -26,14 +29,20 @@ pub(crate) struct FileUnpacked {
     destination_dir: WritablePath,
     unpacker: Unpacker,
     generic_check: GenericChecker,
+    // expected checksums of the source
+    checksums: Vec<Checksum>,
 }
 
 // [[file_unpacked]]
 // source = "file.zip"
 // destination_dir = "path to unpack to"
 // unpacker = "zip"  #optional, when not discoverable from extension.
+// sha256 = "hex digest of the source" # optional
+// sha512 = "hex digest of the source" # optional
 impl CheckConstructor for FileUnpacked {
     type Output = Self;
+    const KEYS: &'static [&'static str] =
+        &["source", "destination_dir", "unpacker", "sha256", "sha512"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -81,11 +90,14 @@ impl CheckConstructor for FileUnpacked {
             }
         };
 
+        let checksums = Checksum::from_table(&check_table)?;
+
         Ok(Self {
             destination_dir,
             source,
             unpacker,
             generic_check,
+            checksums,
         })
     }
 }
@@ -100,12 +112,23 @@ impl Checker for FileUnpacked {
     fn checker_object(&self) -> String {
         self.source.as_ref().to_string_lossy().to_string()
     }
+
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.destination_dir.as_ref().clone()]
+    }
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         let mut action_messages: Vec<String> = vec![];
 
         if !self.source.exists() {
             return Err(CheckError::String("source file does not exists".into()));
         };
+        if !self.checksums.is_empty() {
+            verify_checksums(
+                &self.checksums,
+                &self.source.to_string(),
+                &self.source.read_to_bytes()?,
+            )?;
+        }
 
         let file_unpack = !self.destination_dir.exists();
 
@@ -153,6 +176,15 @@ impl Checker for FileUnpacked {
 
         Ok(check_result)
     }
+
+    fn patch(&self, _patch: &mut Patch) -> Result<(), CheckError> {
+        if self.check_(false)? == CheckResult::NoFixNeeded {
+            return Ok(());
+        }
+        Err(CheckError::String(
+            "unpacking a file can not be added to a patch".into(),
+        ))
+    }
 }
 
 #[cfg(test)]
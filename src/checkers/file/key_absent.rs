@@ -1,5 +1,7 @@
+use std::path::PathBuf;
+
 use crate::{
-    checkers::{base::CheckDefinitionError, file::FileCheck},
+    checkers::{base::CheckDefinitionError, file::FileCheck, patch::Patch},
     mapping::generic::Mapping,
 };
 
@@ -19,6 +21,7 @@ pub(crate) struct KeyAbsent {
 // key.key_to_remove = {}
 impl CheckConstructor for KeyAbsent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["file", "file_type", "indent", "key"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -65,6 +68,10 @@ impl Checker for KeyAbsent {
         self.file_check.check_object()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
+
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         let mut doc = self.file_check.get_mapping()?;
 
@@ -72,6 +79,11 @@ impl Checker for KeyAbsent {
 
         self.file_check.conclude_check_with_new_doc(doc, fix)
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.file_check
+            .patch_with_new_doc(patch, |doc| unset_key(doc, &self.value))
+    }
 }
 
 fn unset_key(doc: &mut dyn Mapping, table_to_unset: &dyn toml_edit::TableLike) {
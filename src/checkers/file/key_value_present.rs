@@ -1,5 +1,7 @@
+use std::path::PathBuf;
+
 use crate::{
-    checkers::{base::CheckDefinitionError, file::FileCheck},
+    checkers::{base::CheckDefinitionError, file::FileCheck, patch::Patch},
     mapping::generic::Mapping,
 };
 
@@ -19,6 +21,7 @@ pub(crate) struct KeyValuePresent {
 // key.key = "value"
 impl CheckConstructor for KeyValuePresent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["file", "file_type", "indent", "key"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -58,6 +61,10 @@ impl Checker for KeyValuePresent {
         self.file_check.check_object()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
+
     fn generic_checker(&self) -> &GenericChecker {
         &self.file_check.generic_check
     }
@@ -69,6 +76,11 @@ impl Checker for KeyValuePresent {
 
         self.file_check.conclude_check_with_new_doc(doc, fix)
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.file_check
+            .patch_with_new_doc(patch, |doc| set_key_value(doc, &self.key_value))
+    }
 }
 
 pub fn set_key_value(doc: &mut dyn Mapping, table_to_set: &dyn toml_edit::TableLike) {
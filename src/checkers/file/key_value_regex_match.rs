@@ -1,9 +1,12 @@
+use std::path::PathBuf;
+
 use regex::Regex;
 
 use crate::{
     checkers::{
         base::{CheckDefinitionError, CheckResult},
         file::{FileCheck, get_option_string_value_from_checktable},
+        patch::Patch,
     },
     file_types::RegexValidateResult,
     mapping::generic::Mapping,
@@ -27,6 +30,7 @@ pub(crate) struct EntryRegexMatched {
 // placeholder = "optional value to be set when key is absent"
 impl CheckConstructor for EntryRegexMatched {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["file", "file_type", "indent", "key", "placeholder"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -68,6 +72,10 @@ impl Checker for EntryRegexMatched {
         self.file_check.check_object()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
+
     fn generic_checker(&self) -> &GenericChecker {
         &self.file_check.generic_check
     }
@@ -117,6 +125,27 @@ impl Checker for EntryRegexMatched {
             }
         }
     }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        // without a placeholder the value can not be fixed
+        if self.placeholder.is_none() {
+            return Ok(());
+        }
+
+        self.file_check.patch_new_contents(patch, |contents| {
+            let mut doc = self.file_check.get_mapping_from(contents)?;
+            match validate_key_value_regex(
+                doc.as_mut(),
+                &self.key_regex,
+                "".to_string(),
+                self.placeholder.clone(),
+            ) {
+                Ok(RegexValidateResult::Valid) => Ok(contents.to_string()),
+                Ok(RegexValidateResult::Invalid { .. }) => doc.to_string(self.file_check.indent),
+                Err(e) => Err(CheckError::InvalidRegex(e.to_string())),
+            }
+        })
+    }
 }
 
 fn make_key_path(parent: &str, key: &str) -> String {
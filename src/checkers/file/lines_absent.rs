@@ -1,10 +1,11 @@
+use std::path::PathBuf;
+
 use crate::checkers::{
     file::FileCheck,
     get_option_boolean_from_check_table,
-    utils::{
-        get_lines_from_check_table, get_marker_from_check_table, remove_between_markers,
-        replace_vars,
-    },
+    patch::Patch,
+    template::render,
+    utils::{get_lines_from_check_table, get_marker_from_check_table, remove_between_markers},
 };
 
 use super::super::base::CheckConstructor;
@@ -24,9 +25,18 @@ pub(crate) struct LinesAbsent {
 // file = "file"
 // lines = "lines"    # lines or marker must be given
 // marker = "marker"
-// is_template = false  # optional, default to to false. true for replace ${var}
+// is_template = false  # optional, default to to false. true to render lines as template
 impl CheckConstructor for LinesAbsent {
     type Output = LinesAbsent;
+    const KEYS: &'static [&'static str] = &[
+        "file",
+        "file_type",
+        "indent",
+        "lines",
+        "marker",
+        "is_template",
+    ];
+
     fn from_check_table(
         generic_check: GenericChecker,
         check_table: toml_edit::Table,
@@ -37,7 +47,16 @@ impl CheckConstructor for LinesAbsent {
         let is_template =
             get_option_boolean_from_check_table(&check_table, "is_template")?.unwrap_or(false);
         let lines = if is_template {
-            replace_vars(lines.as_str(), &generic_check.variables)
+            render(
+                lines.as_str(),
+                &generic_check.variables,
+                &generic_check.file_with_checks,
+            )
+            .map_err(|e| {
+                CheckDefinitionError::InvalidDefinition(format!(
+                    "template can not be rendered ({e})"
+                ))
+            })?
         } else {
             lines
         };
@@ -61,6 +80,10 @@ impl Checker for LinesAbsent {
         self.file_check.check_object()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
+
     fn generic_checker(&self) -> &GenericChecker {
         &self.file_check.generic_check
     }
@@ -68,18 +91,27 @@ impl Checker for LinesAbsent {
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         let contents = self.file_check.get_file_contents()?;
 
-        let new_contents = if let Some((start_marker, end_marker)) = self.marker_lines.as_ref() {
-            remove_between_markers(&contents, start_marker, end_marker)
+        self.file_check
+            .conclude_check_new_contents(self.new_contents(&contents), fix)
+    }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.file_check
+            .patch_new_contents(patch, |contents| Ok(self.new_contents(contents)))
+    }
+}
+
+impl LinesAbsent {
+    fn new_contents(&self, contents: &str) -> String {
+        if let Some((start_marker, end_marker)) = self.marker_lines.as_ref() {
+            remove_between_markers(contents, start_marker, end_marker)
         } else {
             // remove with leading new line when a block is in front of it
-            let mut contents = contents.clone();
+            let mut contents = contents.to_string();
             contents = contents.replace(format!("\n{}", self.lines).as_str(), "");
             contents = contents.replace(self.lines.as_str(), "");
             contents
-        };
-
-        self.file_check
-            .conclude_check_new_contents(new_contents, fix)
+        }
     }
 }
 
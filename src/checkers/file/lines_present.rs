@@ -1,10 +1,14 @@
+use std::path::PathBuf;
+
 use crate::{
     checkers::{
         file::{FileCheck, get_readable_path_from_checktable},
         get_option_boolean_from_check_table,
+        patch::Patch,
+        template::render,
         utils::{
             append_str, get_lines_from_check_table, get_marker_from_check_table,
-            replace_between_markers, replace_vars,
+            replace_between_markers,
         },
     },
     uri::ReadPath,
@@ -50,9 +54,20 @@ pub(crate) fn get_replacement_regex_from_check_table(
 // marker = "marker"       # marker or replacement_regex may be present. Both may be absent. Both may not be present
 // source = "file path"    # optional path to file with the lines
 // replacement_regex = "regex"
-// is_template = false  # optional, default to to false. true for replace ${var}
+// is_template = false  # optional, default to to false. true to render lines as template
 impl CheckConstructor for LinesPresent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &[
+        "file",
+        "file_type",
+        "indent",
+        "lines",
+        "marker",
+        "source",
+        "replacement_regex",
+        "is_template",
+    ];
+
     fn from_check_table(
         generic_check: GenericChecker,
         check_table: toml_edit::Table,
@@ -70,7 +85,16 @@ impl CheckConstructor for LinesPresent {
         let is_template =
             get_option_boolean_from_check_table(&check_table, "is_template")?.unwrap_or(false);
         let lines = if is_template {
-            replace_vars(lines.as_str(), &generic_check.variables)
+            render(
+                lines.as_str(),
+                &generic_check.variables,
+                &generic_check.file_with_checks,
+            )
+            .map_err(|e| {
+                CheckDefinitionError::InvalidDefinition(format!(
+                    "template can not be rendered ({e})"
+                ))
+            })?
         } else {
             lines
         };
@@ -103,6 +127,10 @@ impl Checker for LinesPresent {
         self.file_check.check_object()
     }
 
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.file_check.file_to_check().clone()]
+    }
+
     fn generic_checker(&self) -> &GenericChecker {
         &self.file_check.generic_check
     }
@@ -110,31 +138,40 @@ impl Checker for LinesPresent {
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         let contents = self.file_check.get_file_contents()?;
 
-        let new_contents = match (self.replacement_regex.as_ref(), self.marker_lines.as_ref()) {
+        self.file_check
+            .conclude_check_new_contents(self.new_contents(&contents), fix)
+    }
+
+    fn patch(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        self.file_check
+            .patch_new_contents(patch, |contents| Ok(self.new_contents(contents)))
+    }
+}
+
+impl LinesPresent {
+    fn new_contents(&self, contents: &str) -> String {
+        match (self.replacement_regex.as_ref(), self.marker_lines.as_ref()) {
             (None, None) => {
                 if contents.contains(&self.lines) {
-                    contents.clone()
+                    contents.to_string()
                 } else {
-                    append_str(&contents, &self.lines)
+                    append_str(contents, &self.lines)
                 }
             }
             (Some(regex), None) => {
                 if contents.contains(&self.lines) {
-                    contents.clone()
-                } else if regex.is_match(&contents) {
-                    Regex::replace(regex, &contents, self.lines.trim_end()).to_string()
+                    contents.to_string()
+                } else if regex.is_match(contents) {
+                    Regex::replace(regex, contents, self.lines.trim_end()).to_string()
                 } else {
-                    append_str(&contents, &self.lines)
+                    append_str(contents, &self.lines)
                 }
             }
             (None, Some((start_marker, end_marker))) => {
-                replace_between_markers(&contents, start_marker, end_marker, &self.lines)
+                replace_between_markers(contents, start_marker, end_marker, &self.lines)
             }
             _ => panic!(),
-        };
-
-        self.file_check
-            .conclude_check_new_contents(new_contents, fix)
+        }
     }
 }
 
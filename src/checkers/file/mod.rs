@@ -9,6 +9,7 @@ use crate::{
     checkers::{
         GenericChecker,
         base::{CheckDefinitionError, CheckError, CheckResult},
+        patch::Patch,
     },
     file_types::{self, FileType},
     mapping::generic::Mapping,
@@ -191,12 +192,9 @@ impl FileCheck {
             (false, _) => CheckResult::NoFixNeeded,
             (true, false) => CheckResult::FixNeeded(action_message),
             (true, true) => {
-                let contents = if fix_placeholder {
-                    placeholder.unwrap()
-                } else {
-                    "".to_string()
-                };
-                self.set_file_contents(contents)?;
+                if let Some(contents) = file_exists_new_contents(create_file, placeholder) {
+                    self.set_file_contents(contents)?;
+                }
                 if fix_permissions {
                     fs::set_permissions(self.file_to_check(), permissions.unwrap())?;
                 }
@@ -255,16 +253,59 @@ impl FileCheck {
         Ok(check_result)
     }
 
+    fn patch_new_contents(
+        &self,
+        patch: &mut Patch,
+        new_contents: impl FnOnce(&str) -> Result<String, CheckError>,
+    ) -> Result<(), CheckError> {
+        let contents = match patch.contents(self.file_to_check())? {
+            Some(contents) => with_trailing_newline(contents),
+            None => "".to_string(),
+        };
+
+        let new_contents = new_contents(&contents)?;
+        if new_contents != contents {
+            patch.set_contents(self.file_to_check(), Some(new_contents))?;
+        }
+
+        Ok(())
+    }
+
+    fn patch_with_new_doc(
+        &self,
+        patch: &mut Patch,
+        update_doc: impl FnOnce(&mut dyn Mapping),
+    ) -> Result<(), CheckError> {
+        self.patch_new_contents(patch, |contents| {
+            let mut doc = self.get_mapping_from(contents)?;
+            update_doc(doc.as_mut());
+            doc.to_string(self.indent)
+        })
+    }
+
+    fn patch_file_exists(
+        &self,
+        patch: &mut Patch,
+        placeholder: Option<String>,
+    ) -> Result<(), CheckError> {
+        if let Some(new_contents) =
+            file_exists_new_contents(!patch.exists(self.file_to_check()), placeholder)
+        {
+            patch.set_contents(self.file_to_check(), Some(new_contents))?;
+        }
+        Ok(())
+    }
+
+    fn patch_remove(&self, patch: &mut Patch) -> Result<(), CheckError> {
+        if patch.exists(self.file_to_check()) {
+            patch.set_contents(self.file_to_check(), None)?;
+        }
+        Ok(())
+    }
+
     fn get_file_contents(&self) -> Result<String, CheckError> {
         match fs::read_to_string(self.file_to_check()) {
-            Ok(contents) => {
-                let contents = if contents.ends_with_newline() {
-                    contents
-                } else {
-                    format!("{contents}\n")
-                };
-                Ok(contents)
-            }
+            Ok(contents) => Ok(with_trailing_newline(contents)),
             Err(_) => Ok("".to_string()),
         }
     }
@@ -282,7 +323,7 @@ impl FileCheck {
             fs::create_dir_all(parent)?;
         }
 
-        if let Err(e) = fs::write(self.file_to_check(), contents) {
+        if let Err(e) = self.file_to_check.write_from_string(&contents) {
             log::error!(
                 "⚠  Cannot write file {} {}",
                 self.file_to_check().to_string_lossy(),
@@ -308,6 +349,10 @@ impl FileCheck {
     }
 
     fn get_mapping(&self) -> Result<Box<dyn Mapping>, CheckError> {
+        self.get_mapping_from(&self.get_file_contents()?)
+    }
+
+    fn get_mapping_from(&self, contents: &str) -> Result<Box<dyn Mapping>, CheckError> {
         let extension = self.file_to_check().extension();
         if extension.is_none() && self.file_type_override.is_none() {
             return Err(CheckError::UnknownFileType(
@@ -315,8 +360,6 @@ impl FileCheck {
             ));
         };
 
-        let contents = self.get_file_contents()?;
-
         let extension = self.file_type_override.clone().unwrap_or(
             extension
                 .expect("file has an extension")
@@ -326,16 +369,30 @@ impl FileCheck {
         );
 
         if extension == "toml" {
-            return file_types::toml::Toml::new().to_mapping(&contents);
+            return file_types::toml::Toml::new().to_mapping(contents);
         } else if extension == "json" {
-            return file_types::json::Json::new().to_mapping(&contents);
+            return file_types::json::Json::new().to_mapping(contents);
         } else if extension == "yaml" || extension == "yml" {
-            return file_types::yaml::Yaml::new().to_mapping(&contents);
+            return file_types::yaml::Yaml::new().to_mapping(contents);
         }
         Err(CheckError::UnknownFileType(extension))
     }
 }
 
+/// An existing file is never rewritten, also not when it does not match the regex, as its
+/// contents can not be derived from the regex.
+fn file_exists_new_contents(create_file: bool, placeholder: Option<String>) -> Option<String> {
+    create_file.then(|| placeholder.unwrap_or_default())
+}
+
+fn with_trailing_newline(contents: String) -> String {
+    if contents.ends_with_newline() {
+        contents
+    } else {
+        format!("{contents}\n")
+    }
+}
+
 pub(crate) fn get_option_string_value_from_checktable(
     check_table: &toml_edit::Table,
     key: &str,
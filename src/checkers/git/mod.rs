@@ -38,6 +38,7 @@ fn exactly_one(a: bool, b: bool, c: bool) -> bool {
 // ref = "branch, commit hash or tag"
 impl CheckConstructor for GitFetched {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &["dir", "repo", "branch", "tag", "commit_hash"];
 
     fn from_check_table(
         generic_check: GenericChecker,
@@ -76,6 +77,10 @@ impl Checker for GitFetched {
     fn checker_object(&self) -> String {
         self.repo.clone()
     }
+
+    fn touched_paths(&self) -> Vec<PathBuf> {
+        vec![self.destination_dir.as_ref().clone()]
+    }
     fn check_(&self, fix: bool) -> Result<crate::checkers::base::CheckResult, CheckError> {
         let mut action_messages: Vec<String> = vec![];
 
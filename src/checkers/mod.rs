@@ -1,18 +1,35 @@
-use std::{collections::HashMap, env, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    path::PathBuf,
+    str::FromStr,
+};
 
 use base::CheckConstructor;
 use url::Url;
 
-use crate::uri::{ReadPath, ReadablePath};
+use crate::{
+    glob, http,
+    uri::{ReadPath, ReadablePath, WritablePath},
+};
 
 use self::base::{CheckDefinitionError, Checker};
 
 pub(crate) mod base;
+pub(crate) mod checksum;
+pub(crate) mod dependencies;
 pub(crate) mod file;
 pub(crate) mod git;
 pub(crate) mod package;
+pub(crate) mod patch;
+pub(crate) mod profiles;
+pub(crate) mod report;
+pub(crate) mod template;
 pub(crate) mod test_helpers;
 pub(crate) mod utils;
+pub(crate) mod variables;
+pub(crate) mod verify;
+pub(crate) mod when;
 
 pub(crate) trait RelativeUrl {
     fn short_url_str(&self) -> String;
@@ -20,8 +37,10 @@ pub(crate) trait RelativeUrl {
 
 impl RelativeUrl for ReadablePath {
     fn short_url_str(&self) -> String {
+        // the url of the cwd ends with a slash, otherwise the last dir of the cwd is part of the
+        // relative url
         let cwd_url = url::Url::parse(&format!(
-            "file://{}",
+            "file://{}/",
             env::current_dir()
                 .unwrap()
                 .into_os_string()
@@ -44,10 +63,18 @@ pub(crate) struct GenericChecker {
     pub(crate) tags: Vec<String>,
     // check_only
     pub(crate) check_only: bool,
+    // identifier of the checker, to be referred to by depends_on of other checkers
+    pub(crate) id: Option<String>,
+    // identifiers of the checkers which must be executed before this checker
+    pub(crate) depends_on: Vec<String>,
+    // condition which must be met to execute the checker
+    pub(crate) when: Option<when::When>,
     // variables which are present and can be used for templating
     // this is a owned hashmap to make sure that only variables
     // which are read before the definition of this checker are used
     pub(crate) variables: HashMap<String, String>,
+    // line and column (both starting at 1) of the check table in the file with checks
+    pub(crate) location: Option<(usize, usize)>,
 }
 
 impl GenericChecker {
@@ -59,31 +86,53 @@ impl GenericChecker {
 fn read_tags_from_table(
     check_table: &toml_edit::Table,
 ) -> Result<Vec<String>, CheckDefinitionError> {
-    let mut tags = Vec::new();
-    match check_table.get("tags") {
-        None => Ok(tags),
+    read_string_array_from_table(check_table, "tags")
+}
+
+pub(crate) fn read_string_array_from_table(
+    check_table: &toml_edit::Table,
+    key: &str,
+) -> Result<Vec<String>, CheckDefinitionError> {
+    let mut values = Vec::new();
+    match check_table.get(key) {
+        None => Ok(values),
         Some(item) => {
             if !item.is_array() {
-                Err(CheckDefinitionError::InvalidDefinition(
-                    "`tags` is not an array".into(),
-                ))
+                Err(CheckDefinitionError::InvalidDefinition(format!(
+                    "`{key}` is not an array"
+                )))
             } else {
                 for i in item.as_array().unwrap() {
                     if let Some(value) = i.as_str() {
-                        tags.push(value.into());
+                        values.push(value.into());
                     } else {
-                        return Err(CheckDefinitionError::InvalidDefinition(
-                            "`tags` contains a value which is not a string".to_string(),
-                        ));
+                        return Err(CheckDefinitionError::InvalidDefinition(format!(
+                            "`{key}` contains a value which is not a string"
+                        )));
                     };
                 }
 
-                Ok(tags)
+                Ok(values)
             }
         }
     }
 }
 
+pub(crate) fn get_option_string_from_check_table(
+    check_table: &toml_edit::Table,
+    key: &str,
+) -> Result<Option<String>, CheckDefinitionError> {
+    match check_table.get(key) {
+        None => Ok(None),
+        Some(value) => match value.as_str() {
+            Some(value) => Ok(Some(value.to_string())),
+            None => Err(CheckDefinitionError::InvalidDefinition(format!(
+                "{key} is not a string",
+            ))),
+        },
+    }
+}
+
 fn get_option_boolean_from_check_table(
     check_table: &toml_edit::Table,
     key: &str,
@@ -99,108 +148,518 @@ fn get_option_boolean_from_check_table(
     }
 }
 
-fn get_check_from_check_table(
+const GENERIC_KEYS: &[&str] = &[
+    "tags",
+    "check_only",
+    "id",
+    "depends_on",
+    "when",
+    "require_match",
+];
+
+const TEMPLATED_KEYS: &[&str] = &[
+    "file",
+    "dir",
+    "source",
+    "destination",
+    "destination_dir",
+    "repo",
+    "branch",
+    "version",
+];
+
+fn get_templated_table_key(check_type: &str) -> Option<&'static str> {
+    match check_type {
+        "key_value_present" => Some("key"),
+        "entry_present" => Some("entry"),
+        _ => None,
+    }
+}
+
+fn render_templated_fields(
+    check_type: &str,
+    check_table: &mut toml_edit::Table,
+    generic_check: &GenericChecker,
+) {
+    let table_key = get_templated_table_key(check_type);
+    for (key, item) in check_table.iter_mut() {
+        let key = key.get();
+        let is_templated = match table_key == Some(key) {
+            true => true,
+            false => TEMPLATED_KEYS.contains(&key) && item.is_str(),
+        };
+        if !is_templated {
+            continue;
+        }
+        template::substitute_item(item, &generic_check.variables);
+    }
+}
+
+fn get_glob_key(check_type: &str, keys: &[&str]) -> Option<&'static str> {
+    match check_type {
+        "dir_absent" | "dir_present" => Some("dir"),
+        "file_copied" => Some("source"),
+        _ if keys.contains(&"file") => Some("file"),
+        _ => None,
+    }
+}
+
+/// A `config:` source is relative to the checkers file.
+fn get_glob_path(
+    key: &str,
+    pattern: &str,
+    file_with_checks: &ReadablePath,
+) -> Result<PathBuf, CheckDefinitionError> {
+    let not_local = || {
+        CheckDefinitionError::InvalidDefinition(format!(
+            "{key} ({pattern}) is a glob, which is only supported for local paths"
+        ))
+    };
+    match pattern.strip_prefix("config:") {
+        Some(relative) if key == "source" => file_with_checks
+            .as_ref()
+            .to_file_path()
+            .ok()
+            .and_then(|path| path.parent().map(|dir| dir.join(relative)))
+            .ok_or_else(not_local),
+        _ if Url::parse(pattern).is_ok() => Err(not_local()),
+        _ => WritablePath::from_string(pattern)
+            .map(|path| path.as_ref().clone())
+            .map_err(|_| {
+                CheckDefinitionError::InvalidDefinition(format!("{key} ({pattern}) is invalid"))
+            }),
+    }
+}
+
+fn expand_glob(
+    check_type: &str,
+    keys: &[&str],
+    check_table: toml_edit::Table,
+    generic_check: &GenericChecker,
+) -> Result<Vec<toml_edit::Table>, CheckDefinitionError> {
+    let Some(key) = get_glob_key(check_type, keys) else {
+        return Ok(vec![check_table]);
+    };
+    let Some(pattern) = check_table
+        .get(key)
+        .and_then(|pattern| pattern.as_str())
+        .filter(|pattern| glob::is_pattern(pattern))
+        .map(str::to_string)
+    else {
+        return Ok(vec![check_table]);
+    };
+
+    if generic_check.id.is_some() {
+        return Err(CheckDefinitionError::InvalidDefinition(format!(
+            "id can not be used with a glob in {key}, as it may match more than one path"
+        )));
+    }
+    if key == "source" && check_table.contains_key("destination") {
+        return Err(CheckDefinitionError::InvalidDefinition(
+            "a glob in source needs destination_dir instead of destination".into(),
+        ));
+    }
+
+    let glob_path = get_glob_path(key, &pattern, &generic_check.file_with_checks)?;
+    // a path with brackets in its name, like `app/[slug]/page.tsx`, is not a glob when it exists,
+    // or when the brackets are its only wildcard and they do not match
+    if glob_path.symlink_metadata().is_ok() {
+        return Ok(vec![check_table]);
+    }
+    let paths = match glob::expand(&glob_path) {
+        Ok(paths) if paths.is_empty() && !glob::has_wildcard(&pattern) => {
+            return Ok(vec![check_table]);
+        }
+        Err(_) if !glob::has_wildcard(&pattern) => return Ok(vec![check_table]),
+        Ok(paths) => paths,
+        Err(e) => {
+            return Err(CheckDefinitionError::InvalidDefinition(format!(
+                "{key} is invalid ({e})"
+            )));
+        }
+    };
+    if paths.is_empty() {
+        if get_option_boolean_from_check_table(&check_table, "require_match")?.unwrap_or(false) {
+            return Err(CheckDefinitionError::InvalidDefinition(format!(
+                "{key} ({pattern}) does not match any path"
+            )));
+        }
+        log::warn!("⚠ {key} ({pattern}) does not match any path, so it has no checkers");
+    }
+
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let mut check_table = check_table.clone();
+            check_table.insert(key, path.to_string_lossy().to_string().into());
+            check_table
+        })
+        .collect())
+}
+
+type Constructor =
+    fn(GenericChecker, toml_edit::Table) -> Result<Box<dyn Checker>, CheckDefinitionError>;
+
+fn constructor<C>() -> (&'static [&'static str], Constructor)
+where
+    C: CheckConstructor<Output = C> + Checker + 'static,
+{
+    (C::KEYS, |generic_check, check_table| {
+        Ok(Box::new(C::from_check_table(generic_check, check_table)?))
+    })
+}
+
+fn get_constructor(check_type: &str) -> Option<(&'static [&'static str], Constructor)> {
+    match check_type {
+        "entry_absent" => Some(constructor::<file::entry_absent::EntryAbsent>()),
+        "entry_present" => Some(constructor::<file::entry_present::EntryPresent>()),
+        "file_absent" => Some(constructor::<file::file_absent::FileAbsent>()),
+        "file_present" => Some(constructor::<file::file_present::FilePresent>()),
+        "file_copied" => Some(constructor::<file::file_copied::FileCopied>()),
+        "dir_copied" => Some(constructor::<file::dir_copied::DirCopied>()),
+        "dir_present" => Some(constructor::<file::dir_present::DirPresent>()),
+        "dir_absent" => Some(constructor::<file::dir_absent::DirAbsent>()),
+        "file_unpacked" => Some(constructor::<file::file_unpacked::FileUnpacked>()),
+        "lines_absent" => Some(constructor::<file::lines_absent::LinesAbsent>()),
+        "lines_present" => Some(constructor::<file::lines_present::LinesPresent>()),
+        "package_present" => Some(constructor::<package::package_present::PackagePresent>()),
+        "package_absent" => Some(constructor::<package::package_absent::PackageAbsent>()),
+        "key_value_present" => Some(constructor::<file::key_value_present::KeyValuePresent>()),
+        "key_absent" => Some(constructor::<file::key_absent::KeyAbsent>()),
+        "key_value_regex_matched" => {
+            Some(constructor::<file::key_value_regex_match::EntryRegexMatched>())
+        }
+        "git_fetched" => Some(constructor::<git::GitFetched>()),
+        _ => None,
+    }
+}
+
+fn get_suggested_key(unknown_key: &str, keys: &[&'static str]) -> Option<&'static str> {
+    keys.iter()
+        .chain(GENERIC_KEYS.iter())
+        .map(|key| (strsim::damerau_levenshtein(unknown_key, key), *key))
+        .filter(|(distance, key)| *distance <= (key.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, key)| key)
+}
+
+fn get_unknown_key_errors(
+    check_type: &str,
+    check_table: &toml_edit::Table,
+) -> Vec<(String, CheckDefinitionError)> {
+    let Some((keys, _)) = get_constructor(check_type) else {
+        return vec![];
+    };
+    check_table
+        .iter()
+        .map(|(key, _)| key)
+        .filter(|key| !keys.contains(key) && !GENERIC_KEYS.contains(key))
+        .map(|key| {
+            let message = match get_suggested_key(key, keys) {
+                Some(suggested_key) => {
+                    format!("unknown key `{key}` for {check_type}, did you mean `{suggested_key}`?")
+                }
+                None => format!("unknown key `{key}` for {check_type}"),
+            };
+            (
+                key.to_string(),
+                CheckDefinitionError::InvalidDefinition(message),
+            )
+        })
+        .collect()
+}
+
+fn get_include(
+    include: &toml_edit::Value,
+) -> Result<(String, Vec<checksum::Checksum>), CheckDefinitionError> {
+    match include {
+        toml_edit::Value::String(include_uri) => Ok((include_uri.value().clone(), vec![])),
+        toml_edit::Value::InlineTable(include) => {
+            if let Some((key, _)) = include
+                .iter()
+                .find(|(key, _)| *key != "uri" && !checksum::CHECKSUM_KEYS.contains(key))
+            {
+                return Err(CheckDefinitionError::InvalidDefinition(format!(
+                    "unknown key `{key}` for include"
+                )));
+            }
+            let Some(include_uri) = include.get("uri").and_then(|uri| uri.as_str()) else {
+                return Err(CheckDefinitionError::InvalidDefinition(
+                    "include has no uri".into(),
+                ));
+            };
+            Ok((
+                include_uri.to_string(),
+                checksum::Checksum::from_table(include)?,
+            ))
+        }
+        _ => Err(CheckDefinitionError::InvalidDefinition(
+            "include is not a string or an inline table".into(),
+        )),
+    }
+}
+
+fn get_checks_from_check_table(
     file_with_checks: &ReadablePath,
     check_type: &str,
     check_table: &toml_edit::Table,
     variables: HashMap<String, String>,
-) -> Result<Box<dyn Checker>, CheckDefinitionError> {
-    let check_table = check_table.clone();
+    location: Option<(usize, usize)>,
+) -> Result<Vec<Box<dyn Checker>>, CheckDefinitionError> {
+    let mut check_table = check_table.clone();
+
+    let Some((keys, construct)) = get_constructor(check_type) else {
+        return Err(CheckDefinitionError::UnknownCheckType(
+            check_type.to_string(),
+        ));
+    };
 
     let tags = read_tags_from_table(&check_table)?;
 
     let check_only =
         (get_option_boolean_from_check_table(&check_table, "check_only")?).unwrap_or(false);
 
+    let id = get_option_string_from_check_table(&check_table, "id")?;
+    let depends_on = read_string_array_from_table(&check_table, "depends_on")?;
+    let when = get_option_string_from_check_table(&check_table, "when")?
+        .map(|expression| when::When::parse(&expression))
+        .transpose()?;
+
     let generic_check = GenericChecker {
         file_with_checks: file_with_checks.clone(),
         tags,
         check_only,
+        id,
+        depends_on,
+        when,
         variables,
+        location,
     };
-    match check_type {
-        "entry_absent" => Ok(Box::new(file::entry_absent::EntryAbsent::from_check_table(
-            generic_check,
-            check_table,
-        )?)),
-        "entry_present" => Ok(Box::new(
-            file::entry_present::EntryPresent::from_check_table(generic_check, check_table)?,
-        )),
-        "file_absent" => Ok(Box::new(file::file_absent::FileAbsent::from_check_table(
-            generic_check,
-            check_table,
-        )?)),
-        "file_present" => Ok(Box::new(file::file_present::FilePresent::from_check_table(
-            generic_check,
-            check_table,
-        )?)),
-        "file_copied" => Ok(Box::new(file::file_copied::FileCopied::from_check_table(
-            generic_check,
-            check_table,
-        )?)),
-        "dir_copied" => Ok(Box::new(file::dir_copied::DirCopied::from_check_table(
-            generic_check,
-            check_table,
-        )?)),
-        "dir_present" => Ok(Box::new(file::dir_present::DirPresent::from_check_table(
-            generic_check,
-            check_table,
-        )?)),
-        "dir_absent" => Ok(Box::new(file::dir_absent::DirAbsent::from_check_table(
-            generic_check,
-            check_table,
-        )?)),
-        "file_unpacked" => Ok(Box::new(
-            file::file_unpacked::FileUnpacked::from_check_table(generic_check, check_table)?,
-        )),
-        "lines_absent" => Ok(Box::new(file::lines_absent::LinesAbsent::from_check_table(
-            generic_check,
-            check_table,
-        )?)),
-        "lines_present" => Ok(Box::new(
-            file::lines_present::LinesPresent::from_check_table(generic_check, check_table)?,
-        )),
-        "package_present" => Ok(Box::new(
-            package::package_present::PackagePresent::from_check_table(generic_check, check_table)?,
-        )),
-        "package_absent" => Ok(Box::new(
-            package::package_absent::PackageAbsent::from_check_table(generic_check, check_table)?,
-        )),
-        "key_value_present" => Ok(Box::new(
-            file::key_value_present::KeyValuePresent::from_check_table(
-                generic_check,
-                check_table.clone(),
-            )?,
-        )),
-        "key_absent" => Ok(Box::new(file::key_absent::KeyAbsent::from_check_table(
-            generic_check,
-            check_table.clone(),
-        )?)),
-        "key_value_regex_matched" => Ok(Box::new(
-            file::key_value_regex_match::EntryRegexMatched::from_check_table(
-                generic_check,
-                check_table.clone(),
-            )?,
-        )),
-        "git_fetched" => Ok(Box::new(git::GitFetched::from_check_table(
-            generic_check,
-            check_table.clone(),
-        )?)),
-        _ => {
-            log::error!("unknown check {check_type} {check_table}");
-            Err(CheckDefinitionError::UnknownCheckType(
-                check_type.to_string(),
-            ))
+    render_templated_fields(check_type, &mut check_table, &generic_check);
+
+    expand_glob(check_type, keys, check_table, &generic_check)?
+        .into_iter()
+        .map(|check_table| construct(generic_check.clone(), check_table))
+        .collect()
+}
+
+#[derive(Debug)]
+pub(crate) struct DefinitionIssue {
+    pub(crate) file_with_checks: ReadablePath,
+    // line and column (both starting at 1) of the definition with the problem
+    pub(crate) location: Option<(usize, usize)>,
+    pub(crate) error: CheckDefinitionError,
+}
+
+impl std::fmt::Display for DefinitionIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(
+                f,
+                "{}:{line}:{column}: {}",
+                self.file_with_checks.short_url_str(),
+                self.error
+            ),
+            None => write!(
+                f,
+                "{}: {}",
+                self.file_with_checks.short_url_str(),
+                self.error
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CheckDefinitions {
+    pub(crate) checks: Vec<Box<dyn Checker>>,
+    pub(crate) profiles: BTreeMap<String, profiles::Profile>,
+    pub(crate) issues: Vec<DefinitionIssue>,
+}
+
+impl CheckDefinitions {
+    fn extend(&mut self, other: CheckDefinitions) {
+        self.checks.extend(other.checks);
+        self.profiles.extend(other.profiles);
+        self.issues.extend(other.issues);
+    }
+
+    fn add_issue(
+        &mut self,
+        file_with_checks: &ReadablePath,
+        location: Option<(usize, usize)>,
+        error: CheckDefinitionError,
+    ) {
+        self.issues.push(DefinitionIssue {
+            file_with_checks: file_with_checks.clone(),
+            location,
+            error,
+        })
+    }
+}
+
+fn get_line_and_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .expect("rsplit gives at least one item")
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+struct Locator {
+    contents: String,
+    table: Option<toml_edit::Table>,
+}
+
+impl Locator {
+    fn new(contents: &str, top_level_keys: &[&str]) -> Self {
+        let mut table = toml_edit::Document::parse(contents.to_string())
+            .ok()
+            .map(|doc| doc.as_table().clone());
+        for key in top_level_keys {
+            table = table.and_then(|t| t.get(key).and_then(|t| t.as_table()).cloned());
+        }
+        Self {
+            contents: contents.to_string(),
+            table,
+        }
+    }
+
+    fn location(&self, span: Option<std::ops::Range<usize>>) -> Option<(usize, usize)> {
+        span.map(|span| get_line_and_column(&self.contents, span.start))
+    }
+
+    fn item(&self, key: &str) -> Option<&toml_edit::Item> {
+        self.table.as_ref().and_then(|table| table.get(key))
+    }
+
+    fn check_table(&self, check_type: &str, index: Option<usize>) -> Option<&toml_edit::Table> {
+        match (self.item(check_type)?, index) {
+            (toml_edit::Item::Table(table), _) => Some(table),
+            (toml_edit::Item::ArrayOfTables(array), Some(index)) => array.get(index),
+            _ => None,
         }
     }
+
+    fn include_location(&self, index: usize) -> Option<(usize, usize)> {
+        let span = self
+            .item("include")
+            .and_then(|include| include.as_array())
+            .and_then(|include| include.get(index))
+            .and_then(|include_uri| include_uri.span());
+        self.location(span).or_else(|| self.key_location("include"))
+    }
+
+    fn key_location(&self, key: &str) -> Option<(usize, usize)> {
+        let table = self.table.as_ref()?;
+        let span = table
+            .key(key)
+            .and_then(|key| key.span())
+            .or_else(|| table.get(key).and_then(|item| item.span()));
+        self.location(span)
+    }
+
+    fn check_location(
+        &self,
+        check_type: &str,
+        index: Option<usize>,
+        key: Option<&str>,
+    ) -> Option<(usize, usize)> {
+        let check_table = self.check_table(check_type, index);
+        let span = match (check_table, key) {
+            (Some(check_table), Some(key)) => check_table
+                .key(key)
+                .and_then(|key| key.span())
+                .or_else(|| check_table.span()),
+            (Some(check_table), None) => check_table.span(),
+            (None, _) => None,
+        };
+        self.location(span)
+            .or_else(|| self.key_location(check_type))
+    }
 }
 
 pub(crate) fn read_checks_from_path(
     file_with_checks: &ReadablePath,
     variables: &mut HashMap<String, String>,
-) -> Vec<Box<dyn Checker>> {
-    let mut checks: Vec<Box<dyn Checker>> = vec![];
+) -> CheckDefinitions {
+    let check_definitions = load_checks_from_path(file_with_checks, variables);
+    for issue in check_definitions.issues.iter() {
+        log::error!("⚠ Checkfile {issue}");
+    }
+    check_definitions
+}
+
+/// Problems in the definitions are collected instead of aborting, so all of them can be reported.
+pub(crate) fn load_checks_from_path(
+    file_with_checks: &ReadablePath,
+    variables: &mut HashMap<String, String>,
+) -> CheckDefinitions {
+    let mut check_definitions = load_checks_with_includes(file_with_checks, variables, true);
+    dependencies::order_by_dependencies(&mut check_definitions);
+    check_definitions
+}
+
+/// The headers of the `"*"` table are added to the requests to all hosts, so they can not
+/// contain credentials.
+fn add_http_headers(
+    http_headers: &toml_edit::Item,
+    locator: &Locator,
+    file_with_checks: &ReadablePath,
+    check_definitions: &mut CheckDefinitions,
+) {
+    let Some(hosts) = http_headers.as_table_like() else {
+        check_definitions.add_issue(
+            file_with_checks,
+            locator.key_location("http_headers"),
+            CheckDefinitionError::InvalidDefinition("http_headers is not a table".into()),
+        );
+        return;
+    };
+    for (host, headers) in hosts.iter() {
+        let location = locator.check_location("http_headers", None, Some(host));
+        let Some(headers) = headers.as_table_like() else {
+            check_definitions.add_issue(
+                file_with_checks,
+                location,
+                CheckDefinitionError::InvalidDefinition(format!(
+                    "http_headers for {host} is not a table"
+                )),
+            );
+            continue;
+        };
+        for (name, value) in headers.iter() {
+            let host = Some(host).filter(|host| *host != "*");
+            let header = match value.as_str() {
+                Some(value) => http::ExtraHeader::new(host, name, value),
+                None => Err(format!("the value of header `{name}` is not a string")),
+            };
+            match header {
+                Ok(header) => http::add_extra_header(header),
+                Err(e) => check_definitions.add_issue(
+                    file_with_checks,
+                    location,
+                    CheckDefinitionError::InvalidDefinition(e),
+                ),
+            }
+        }
+    }
+}
+
+/// `is_root` is set for the file given on the command line.
+fn load_checks_with_includes(
+    file_with_checks: &ReadablePath,
+    variables: &mut HashMap<String, String>,
+    is_root: bool,
+) -> CheckDefinitions {
+    let mut check_definitions = CheckDefinitions::default();
     let mut file_with_checks = file_with_checks.clone();
+
     let checks_toml_str = match file_with_checks.read_to_string() {
         Ok(checks_toml) => checks_toml,
         Err(_) => {
@@ -209,16 +668,27 @@ pub(crate) fn read_checks_from_path(
             ) {
                 Ok(uri) => uri,
                 Err(_) => {
-                    log::error!("⚠ {file_with_checks} could not be read");
-                    return checks;
+                    check_definitions.add_issue(
+                        &file_with_checks,
+                        None,
+                        CheckDefinitionError::FileCanNotBeRead,
+                    );
+                    return check_definitions;
                 }
             };
-            file_with_checks = ReadablePath::from_url(uri);
-            match file_with_checks.read_to_string() {
-                Ok(checks_toml) => checks_toml,
+            let file_with_checks_in_dir = ReadablePath::from_url(uri);
+            match file_with_checks_in_dir.read_to_string() {
+                Ok(checks_toml) => {
+                    file_with_checks = file_with_checks_in_dir;
+                    checks_toml
+                }
                 Err(_) => {
-                    log::error!("⚠ {file_with_checks} could not be read");
-                    return checks;
+                    check_definitions.add_issue(
+                        &file_with_checks,
+                        None,
+                        CheckDefinitionError::FileCanNotBeRead,
+                    );
+                    return check_definitions;
                 }
             }
         }
@@ -228,8 +698,15 @@ pub(crate) fn read_checks_from_path(
         match toml_edit::DocumentMut::from_str(checks_toml_str.as_str()) {
             Ok(checks_toml) => checks_toml.as_table().to_owned(),
             Err(e) => {
-                log::error!("Invalid toml file {file_with_checks} {e}");
-                return checks;
+                let location = e
+                    .span()
+                    .map(|span| get_line_and_column(&checks_toml_str, span.start));
+                check_definitions.add_issue(
+                    &file_with_checks,
+                    location,
+                    CheckDefinitionError::InvalidToml(e.message().to_string()),
+                );
+                return check_definitions;
             }
         };
 
@@ -241,93 +718,257 @@ pub(crate) fn read_checks_from_path(
         vec![]
     };
 
-    for key in top_level_keys {
+    for key in top_level_keys.iter() {
         checks_toml = match checks_toml.get(key) {
             Some(toml) => match toml.as_table() {
                 Some(toml) => toml.clone(),
                 None => {
-                    log::error!("Top level key {key} in {file_with_checks} is not a table");
-                    return vec![];
+                    check_definitions.add_issue(
+                        &file_with_checks,
+                        None,
+                        CheckDefinitionError::InvalidDefinition(format!(
+                            "top level key {key} is not a table"
+                        )),
+                    );
+                    return check_definitions;
                 }
             },
             None => {
-                log::error!("Top level key {key} is not found in {file_with_checks}");
-                return vec![];
+                check_definitions.add_issue(
+                    &file_with_checks,
+                    None,
+                    CheckDefinitionError::InvalidDefinition(format!(
+                        "top level key {key} is not found"
+                    )),
+                );
+                return check_definitions;
             }
         }
     }
 
+    let locator = Locator::new(&checks_toml_str, &top_level_keys);
+    variables::set_check_file_dir(variables, &file_with_checks);
+
+    // the headers are added before the includes are fetched, as they may be needed to fetch them
+    if let Some(http_headers) = checks_toml.remove("http_headers") {
+        // an included or remote file must not be able to send headers, like credentials, to
+        // other hosts
+        if is_root && file_with_checks.as_ref().scheme() == "file" {
+            add_http_headers(
+                &http_headers,
+                &locator,
+                &file_with_checks,
+                &mut check_definitions,
+            );
+        } else {
+            check_definitions.add_issue(
+                &file_with_checks,
+                locator.key_location("http_headers"),
+                CheckDefinitionError::InvalidDefinition(
+                    "http_headers are only allowed in the local checkers file given on the \
+                     command line"
+                        .into(),
+                ),
+            );
+        }
+    }
+
+    // checkers of this file with the position of their table, to restore the order in which
+    // they are written, as the tables are grouped by check type
+    let mut checks = vec![];
+
     for (key, value) in checks_toml {
         if key == "include" {
-            if let toml_edit::Item::Value(toml_edit::Value::Array(include_uris)) = value {
-                for include_uri in include_uris {
-                    let include_path = match ReadablePath::from_string(
-                        include_uri.as_str().expect("uri is a string"),
-                        Some(&file_with_checks),
-                    ) {
-                        Ok(include_path) => include_path,
-                        Err(_) => {
-                            log::error!("{include_uri} is not a valid uri");
-                            std::process::exit(1);
+            match value {
+                toml_edit::Item::Value(toml_edit::Value::Array(include_uris)) => {
+                    for (index, include_uri) in include_uris.iter().enumerate() {
+                        let location = locator.include_location(index);
+                        let (include_uri, checksums) = match get_include(include_uri) {
+                            Ok(include) => include,
+                            Err(err) => {
+                                check_definitions.add_issue(&file_with_checks, location, err);
+                                continue;
+                            }
+                        };
+                        let include_uri = include_uri.as_str();
+                        let include_path =
+                            match ReadablePath::from_string(include_uri, Some(&file_with_checks)) {
+                                Ok(include_path) => include_path,
+                                Err(_) => {
+                                    check_definitions.add_issue(
+                                        &file_with_checks,
+                                        location,
+                                        CheckDefinitionError::InvalidDefinition(format!(
+                                            "{include_uri} is not a valid uri"
+                                        )),
+                                    );
+                                    continue;
+                                }
+                            };
+                        if !checksums.is_empty()
+                            && let Err(e) = include_path.read_to_bytes().and_then(|contents| {
+                                checksum::verify_checksums(&checksums, include_uri, &contents)
+                            })
+                        {
+                            check_definitions.add_issue(
+                                &file_with_checks,
+                                location,
+                                CheckDefinitionError::InvalidDefinition(format!(
+                                    "include {include_uri} is rejected: {e}"
+                                )),
+                            );
+                            continue;
                         }
-                    };
-                    checks.extend(read_checks_from_path(&include_path, variables));
+                        check_definitions.extend(load_checks_with_includes(
+                            &include_path,
+                            variables,
+                            false,
+                        ));
+                        variables::set_check_file_dir(variables, &file_with_checks);
+                    }
                 }
+                _ => check_definitions.add_issue(
+                    &file_with_checks,
+                    locator.key_location("include"),
+                    CheckDefinitionError::InvalidDefinition("include is not an array".into()),
+                ),
             }
 
             continue;
         }
         if key == "variables" {
-            if let toml_edit::Item::Table(current_variables) = &value {
-                current_variables.iter().for_each(|(k, v)| {
-                    let v = v.as_str().expect("value is a string");
-                    // TODO: fix error or convert when value is not a string
-                    variables.insert(k.to_string(), v.to_string());
-                });
+            let Some(current_variables) = value.as_table_like() else {
+                check_definitions.add_issue(
+                    &file_with_checks,
+                    locator.key_location("variables"),
+                    CheckDefinitionError::InvalidDefinition("variables is not a table".into()),
+                );
+                continue;
+            };
+            for (k, v) in current_variables.iter() {
+                if variables::is_reserved(k) {
+                    check_definitions.add_issue(
+                        &file_with_checks,
+                        locator.check_location("variables", None, Some(k)),
+                        CheckDefinitionError::InvalidDefinition(format!(
+                            "variable {k} is reserved for the built-in variables"
+                        )),
+                    );
+                    continue;
+                }
+                if variables::is_overridden(k) {
+                    log::debug!("variable {k} is given by --var");
+                    continue;
+                }
+                match variables::read_variable(v, &file_with_checks) {
+                    Ok(v) => {
+                        variables.insert(k.to_string(), v);
+                    }
+                    Err(e) => check_definitions.add_issue(
+                        &file_with_checks,
+                        locator.check_location("variables", None, Some(k)),
+                        CheckDefinitionError::InvalidDefinition(format!(
+                            "variable {k} can not be read ({e})"
+                        )),
+                    ),
+                }
+            }
+
+            continue;
+        }
+        if key == "profiles" {
+            let toml_edit::Item::Table(profiles) = &value else {
+                check_definitions.add_issue(
+                    &file_with_checks,
+                    locator.key_location("profiles"),
+                    CheckDefinitionError::InvalidDefinition("profiles is not a table".into()),
+                );
+                continue;
+            };
+            for (name, profile) in profiles.iter() {
+                let profile = match profile.as_table() {
+                    Some(profile) => profiles::Profile::from_table(profile),
+                    None => Err(CheckDefinitionError::InvalidDefinition(format!(
+                        "profile {name} is not a table"
+                    ))),
+                };
+                match profile {
+                    Ok(profile) => {
+                        check_definitions.profiles.insert(name.to_string(), profile);
+                    }
+                    Err(err) => check_definitions.add_issue(
+                        &file_with_checks,
+                        locator.check_location("profiles", None, Some(name)),
+                        err,
+                    ),
+                }
             }
 
             continue;
         }
 
         let check_type = key;
-        let mut checks_to_add = vec![];
-        match value {
-            toml_edit::Item::Table(config_table) => {
-                checks_to_add.push(get_check_from_check_table(
+        let check_tables = match value {
+            toml_edit::Item::Table(config_table) => vec![(None, config_table)],
+            toml_edit::Item::ArrayOfTables(array) => array
+                .into_iter()
+                .enumerate()
+                .map(|(index, config_table)| (Some(index), config_table))
+                .collect(),
+            _ => {
+                check_definitions.add_issue(
                     &file_with_checks,
-                    check_type.as_str(),
-                    &config_table,
-                    variables.clone(),
-                ));
+                    locator.key_location(&check_type),
+                    CheckDefinitionError::InvalidDefinition(format!(
+                        "{check_type} is not a table or an array of tables"
+                    )),
+                );
+                continue;
             }
-            toml_edit::Item::ArrayOfTables(array) => {
-                for config_table in array {
-                    checks_to_add.push(get_check_from_check_table(
+        };
+
+        for (index, config_table) in check_tables {
+            let unknown_key_errors = get_unknown_key_errors(check_type.as_str(), &config_table);
+            if !unknown_key_errors.is_empty() {
+                for (unknown_key, err) in unknown_key_errors {
+                    check_definitions.add_issue(
                         &file_with_checks,
-                        check_type.as_str(),
-                        &config_table,
-                        variables.clone(),
-                    ));
+                        locator.check_location(&check_type, index, Some(&unknown_key)),
+                        err,
+                    );
                 }
+                continue;
             }
-            _ => {}
-        }
 
-        for check in checks_to_add {
-            match check {
-                Ok(check) => checks.push(check),
-                Err(err) => {
-                    log::error!("Checkfile {file_with_checks}:{check_type} has errors: {err}")
-                }
+            let location = locator.check_location(&check_type, index, None);
+            match get_checks_from_check_table(
+                &file_with_checks,
+                check_type.as_str(),
+                &config_table,
+                variables.clone(),
+                location,
+            ) {
+                Ok(new_checks) => checks.extend(
+                    new_checks
+                        .into_iter()
+                        .map(|check| (config_table.position(), check)),
+                ),
+                Err(err) => check_definitions.add_issue(&file_with_checks, location, err),
             }
         }
     }
-    checks
+
+    checks.sort_by_key(|(position, _)| *position);
+    check_definitions
+        .checks
+        .extend(checks.into_iter().map(|(_, check)| check));
+    check_definitions
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::checkers::base::CheckResult;
     use std::fs::File;
     use std::io::Write;
     use tempfile::tempdir;
@@ -390,7 +1031,7 @@ entry.key = [1,2,3]
             None,
         )
         .unwrap();
-        let checks = read_checks_from_path(&path_with_checkers, &mut variables);
+        let checks = read_checks_from_path(&path_with_checkers, &mut variables).checks;
 
         assert_eq!(checks.len(), 9);
     }
@@ -417,8 +1058,501 @@ entry.key = [1,2,3]
             None,
         )
         .unwrap();
-        let checks = read_checks_from_path(&path_with_checkers, &mut variables);
+        let checks = read_checks_from_path(&path_with_checkers, &mut variables).checks;
 
         assert_eq!(checks.len(), 0);
     }
+
+    #[test]
+    fn test_load_checks_with_issues_from_path() {
+        let dir = tempdir().unwrap();
+        let path_with_checkers = dir.path().join("check-config.toml");
+        let mut file_with_checkers = File::create(&path_with_checkers).unwrap();
+
+        write!(
+            file_with_checkers,
+            r#"
+[[fileXabsent]]
+file = "test/absent_file"
+
+[[file_present]]
+file = "test/present_file"
+permisions = "644"
+
+[[file_present]]
+file = "test/present_file"
+regex = "[0-9"
+"#
+        )
+        .expect("write is succesful");
+
+        let mut variables = HashMap::new();
+        let path_with_checkers = ReadablePath::from_string(
+            &format!("file://{}", path_with_checkers.to_str().unwrap()),
+            None,
+        )
+        .unwrap();
+        let check_definitions = load_checks_from_path(&path_with_checkers, &mut variables);
+
+        assert_eq!(check_definitions.checks.len(), 0);
+        assert_eq!(
+            check_definitions
+                .issues
+                .iter()
+                .map(|issue| (issue.location, &issue.error))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Some((2, 1)),
+                    &CheckDefinitionError::UnknownCheckType("fileXabsent".into())
+                ),
+                (
+                    Some((7, 1)),
+                    &CheckDefinitionError::InvalidDefinition(
+                        "unknown key `permisions` for file_present, did you mean `permissions`?"
+                            .into()
+                    )
+                ),
+                (
+                    Some((9, 1)),
+                    &CheckDefinitionError::InvalidDefinition(
+                        "regex ([0-9) is not a valid regex".into()
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_templated_fields() {
+        let generic_check = GenericChecker {
+            variables: HashMap::from([
+                ("project".to_string(), "app".to_string()),
+                ("ruff_version".to_string(), "0.6.0".to_string()),
+            ]),
+            ..test_helpers::get_generic_check()
+        };
+        let render = |check_type: &str, check_table: &str| {
+            let mut check_table = toml_edit::DocumentMut::from_str(check_table)
+                .unwrap()
+                .as_table()
+                .clone();
+            render_templated_fields(check_type, &mut check_table, &generic_check);
+            check_table.to_string()
+        };
+
+        assert_eq!(
+            render(
+                "key_value_present",
+                r#"file = "${project}/pyproject.toml"
+key.tool.ruff = { required-version = "${ruff_version}", src = ["${project}"] }
+"#
+            ),
+            r#"file = "app/pyproject.toml"
+key.tool.ruff = { required-version = "0.6.0", src = ["app"] }
+"#
+        );
+        // only the values of key_value_present and entry_present are templated
+        assert_eq!(
+            render("key_absent", "key.tool = \"${project}\"\n"),
+            "key.tool = \"${project}\"\n"
+        );
+        // values which are not a defined variable are kept as is
+        for literal in [
+            "file = \"${workspaceFolder}/.venv/bin/python\"\n",
+            "file = \"${{ secrets.TOKEN }}\"\n",
+            "file = \"out/{{weird}}.txt\"\n",
+        ] {
+            assert_eq!(render("file_present", literal), literal);
+        }
+    }
+
+    #[test]
+    fn test_templated_field_guarded_by_when() {
+        let file_with_checks = ReadablePath::from_string("/tmp/check-config.toml", None).unwrap();
+        let check_table = toml_edit::DocumentMut::from_str(
+            r#"file = "${project}/a.toml"
+when = "var.project"
+"#,
+        )
+        .unwrap()
+        .as_table()
+        .clone();
+
+        let checks = get_checks_from_check_table(
+            &file_with_checks,
+            "file_present",
+            &check_table,
+            HashMap::new(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(checks.len(), 1);
+        assert_eq!(
+            checks[0].skipped_by_condition(),
+            Some(CheckResult::Skipped(
+                "condition `var.project` is not met".into()
+            ))
+        );
+
+        // without the condition the undefined variable is kept as is
+        let mut check_table = check_table;
+        check_table.remove("when");
+        let checks = get_checks_from_check_table(
+            &file_with_checks,
+            "file_present",
+            &check_table,
+            HashMap::new(),
+            None,
+        )
+        .unwrap();
+        assert!(checks[0].checker_object().ends_with("${project}/a.toml"));
+    }
+
+    #[test]
+    fn test_glob_expands_to_a_checker_per_path() {
+        let dir = tempdir().unwrap();
+        for package in ["a", "b"] {
+            std::fs::create_dir_all(dir.path().join("packages").join(package)).unwrap();
+            File::create(
+                dir.path()
+                    .join("packages")
+                    .join(package)
+                    .join("pyproject.toml"),
+            )
+            .unwrap();
+        }
+        let path_with_checkers = dir.path().join("check-config.toml");
+        std::fs::write(
+            &path_with_checkers,
+            format!(
+                r#"
+[[key_value_present]]
+file = "{dir}/packages/*/pyproject.toml"
+key.tool.ruff.line-length = 100
+
+[[file_absent]]
+file = "{dir}/packages/*/setup.py"
+
+[[file_absent]]
+file = "{dir}/packages/*/setup.cfg"
+require_match = true
+
+[[key_absent]]
+id = "ruff"
+file = "{dir}/packages/*/pyproject.toml"
+key.tool.ruff = 1
+"#,
+                dir = dir.path().to_string_lossy()
+            ),
+        )
+        .unwrap();
+
+        let path_with_checkers = ReadablePath::from_string(
+            &format!("file://{}", path_with_checkers.to_str().unwrap()),
+            None,
+        )
+        .unwrap();
+        let check_definitions = load_checks_from_path(&path_with_checkers, &mut HashMap::new());
+
+        assert_eq!(
+            check_definitions
+                .checks
+                .iter()
+                .map(|check| check.touched_paths())
+                .collect::<Vec<_>>(),
+            vec![
+                vec![dir.path().join("packages/a/pyproject.toml")],
+                vec![dir.path().join("packages/b/pyproject.toml")],
+            ]
+        );
+        assert_eq!(
+            check_definitions
+                .issues
+                .iter()
+                .map(|issue| (issue.location, &issue.error))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Some((9, 1)),
+                    &CheckDefinitionError::InvalidDefinition(format!(
+                        "file ({}/packages/*/setup.cfg) does not match any path",
+                        dir.path().to_string_lossy()
+                    ))
+                ),
+                (
+                    Some((13, 1)),
+                    &CheckDefinitionError::InvalidDefinition(
+                        "id can not be used with a glob in file, as it may match more than one \
+                         path"
+                            .into()
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_with_brackets_is_not_a_glob() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("app/[slug]")).unwrap();
+        File::create(dir.path().join("app/[slug]/page.tsx")).unwrap();
+        File::create(dir.path().join("app/s.tsx")).unwrap();
+        let file_with_checks = ReadablePath::from_string("/tmp/check-config.toml", None).unwrap();
+        let touched_paths = |file: &str| {
+            let mut check_table = toml_edit::Table::new();
+            check_table.insert(
+                "file",
+                dir.path().join(file).to_string_lossy().as_ref().into(),
+            );
+            get_checks_from_check_table(
+                &file_with_checks,
+                "file_present",
+                &check_table,
+                HashMap::new(),
+                None,
+            )
+            .unwrap()
+            .iter()
+            .map(|check| check.touched_paths())
+            .collect::<Vec<_>>()
+        };
+
+        // an existing path is kept, even though `[slug]` matches `s` as a glob
+        assert_eq!(
+            touched_paths("app/[slug]/page.tsx"),
+            vec![vec![dir.path().join("app/[slug]/page.tsx")]]
+        );
+        // a path without matches and without `*` or `?` is kept as well
+        assert_eq!(
+            touched_paths("app/[id]/page.tsx"),
+            vec![vec![dir.path().join("app/[id]/page.tsx")]]
+        );
+        assert_eq!(
+            touched_paths("app/[a-z].tsx"),
+            vec![vec![dir.path().join("app/s.tsx")]]
+        );
+        assert_eq!(touched_paths("app/[id]/*.tsx"), Vec::<Vec<PathBuf>>::new());
+    }
+
+    #[test]
+    fn test_include_with_checksum() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("included.toml"),
+            "[[file_present]]\nfile = \"/tmp/included\"\n",
+        )
+        .unwrap();
+        let path_with_checkers = dir.path().join("check-config.toml");
+        std::fs::write(
+            &path_with_checkers,
+            format!(
+                r#"
+include = [
+    {{ uri = "config:included.toml", sha256 = "{}" }},
+    {{ uri = "config:included.toml", sha256 = "{}" }},
+]
+"#,
+                "e89deea464d94eafe163c5edc8c17ba0bab646488f57d906d770793de54be4e6",
+                "0".repeat(64)
+            ),
+        )
+        .unwrap();
+        let path_with_checkers = ReadablePath::from_string(
+            &format!("file://{}", path_with_checkers.to_str().unwrap()),
+            None,
+        )
+        .unwrap();
+
+        let check_definitions = load_checks_from_path(&path_with_checkers, &mut HashMap::new());
+
+        assert_eq!(check_definitions.checks.len(), 1);
+        assert_eq!(check_definitions.issues.len(), 1);
+        assert_eq!(check_definitions.issues[0].location, Some((4, 5)));
+        assert!(check_definitions.issues[0].error.to_string().contains(
+            "include config:included.toml is rejected: sha256 of config:included.toml is"
+        ));
+    }
+
+    #[test]
+    fn test_check_file_dir_of_included_file() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(
+            dir.path().join("sub").join("included.toml"),
+            "[[file_present]]\nfile = \"${cc.check_file_dir}/included\"\n",
+        )
+        .unwrap();
+        let path_with_checkers = dir.path().join("check-config.toml");
+        std::fs::write(
+            &path_with_checkers,
+            r#"
+include = ["config:sub/included.toml"]
+
+[[file_present]]
+file = "${cc.check_file_dir}/root"
+"#,
+        )
+        .unwrap();
+        let path_with_checkers = ReadablePath::from_string(
+            &format!("file://{}", path_with_checkers.to_str().unwrap()),
+            None,
+        )
+        .unwrap();
+
+        let check_definitions = load_checks_from_path(&path_with_checkers, &mut HashMap::new());
+
+        assert!(check_definitions.issues.is_empty());
+        assert_eq!(
+            check_definitions
+                .checks
+                .iter()
+                .map(|check| check.checker_object())
+                .collect::<Vec<_>>(),
+            vec![
+                dir.path().join("sub").join("included").to_string_lossy(),
+                dir.path().join("root").to_string_lossy(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_include_from_git_repo() {
+        let dir = tempdir().unwrap();
+        let repo_dir = dir.path().join("repo");
+        crate::git::tests::create_repo(
+            &repo_dir,
+            &[
+                ("checks/base.toml", "include = [\"config:other.toml\"]\n"),
+                (
+                    "checks/other.toml",
+                    "[[file_present]]\nfile = \"/tmp/from_git\"\n",
+                ),
+            ],
+            "v1",
+        );
+        let path_with_checkers = dir.path().join("check-config.toml");
+        std::fs::write(
+            &path_with_checkers,
+            format!(
+                "include = [\"git+file://{}@v1#checks/base.toml\"]\n",
+                repo_dir.to_string_lossy()
+            ),
+        )
+        .unwrap();
+        let path_with_checkers = ReadablePath::from_string(
+            &format!("file://{}", path_with_checkers.to_str().unwrap()),
+            None,
+        )
+        .unwrap();
+
+        let check_definitions = load_checks_from_path(&path_with_checkers, &mut HashMap::new());
+
+        assert!(check_definitions.issues.is_empty());
+        assert_eq!(check_definitions.checks.len(), 1);
+        assert_eq!(
+            check_definitions.checks[0].checker_object(),
+            "/tmp/from_git"
+        );
+    }
+
+    #[test]
+    fn test_invalid_http_headers() {
+        let dir = tempdir().unwrap();
+        let path_with_checkers = dir.path().join("check-config.toml");
+        std::fs::write(
+            &path_with_checkers,
+            r#"
+[http_headers."artifacts.example.com"]
+"X Api" = "2"
+
+[http_headers."*"]
+X-Api-Version = 2
+Authorization = "Bearer secret"
+"#,
+        )
+        .unwrap();
+        let path_with_checkers = ReadablePath::from_string(
+            &format!("file://{}", path_with_checkers.to_str().unwrap()),
+            None,
+        )
+        .unwrap();
+
+        let check_definitions = load_checks_from_path(&path_with_checkers, &mut HashMap::new());
+
+        assert_eq!(
+            check_definitions
+                .issues
+                .iter()
+                .map(|issue| issue.error.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "invalid check definition (`X Api` is not a valid header name)",
+                "invalid check definition (the value of header `X-Api-Version` is not a string)",
+                "invalid check definition (header `Authorization` contains credentials, so it \
+                 needs a host)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_http_headers_of_included_file() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("included.toml"),
+            "[http_headers.\"*\"]\nX-Api-Version = \"2\"\n",
+        )
+        .unwrap();
+        let path_with_checkers = dir.path().join("check-config.toml");
+        std::fs::write(
+            &path_with_checkers,
+            "include = [\"config:included.toml\"]\n",
+        )
+        .unwrap();
+        let path_with_checkers = ReadablePath::from_string(
+            &format!("file://{}", path_with_checkers.to_str().unwrap()),
+            None,
+        )
+        .unwrap();
+
+        let check_definitions = load_checks_from_path(&path_with_checkers, &mut HashMap::new());
+
+        assert_eq!(
+            check_definitions
+                .issues
+                .iter()
+                .map(|issue| (issue.location, issue.error.to_string()))
+                .collect::<Vec<_>>(),
+            vec![(
+                Some((1, 2)),
+                "invalid check definition (http_headers are only allowed in the local checkers \
+                 file given on the command line)"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_short_url_str() {
+        let cwd = env::current_dir().unwrap();
+        let file_with_checks = ReadablePath::from_string(
+            &format!("file://{}", cwd.join("check-config.toml").to_string_lossy()),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(file_with_checks.short_url_str(), "check-config.toml");
+    }
+
+    #[test]
+    fn test_get_suggested_key() {
+        let keys = file::file_copied::FileCopied::KEYS;
+        assert_eq!(
+            get_suggested_key("destinaton_dir", keys),
+            Some("destination_dir")
+        );
+        assert_eq!(get_suggested_key("is_templat", keys), Some("is_template"));
+        assert_eq!(get_suggested_key("tag", keys), Some("tags"));
+        assert_eq!(get_suggested_key("unrelated", keys), None);
+    }
 }
@@ -16,6 +16,14 @@ pub(crate) struct PackageAbsent {
 
 impl CheckConstructor for PackageAbsent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &[
+        "type",
+        "package",
+        "version",
+        "install_command",
+        "uninstall_command",
+        "version_command",
+    ];
 
     fn from_check_table(
         generic_check: GenericChecker,
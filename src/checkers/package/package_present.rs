@@ -12,6 +12,14 @@ pub(crate) struct PackagePresent {
 
 impl CheckConstructor for PackagePresent {
     type Output = Self;
+    const KEYS: &'static [&'static str] = &[
+        "type",
+        "package",
+        "version",
+        "install_command",
+        "uninstall_command",
+        "version_command",
+    ];
 
     fn from_check_table(
         generic_check: GenericChecker,
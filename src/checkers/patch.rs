@@ -0,0 +1,322 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use similar::TextDiff;
+
+use crate::{checkers::base::CheckError, uri};
+
+/// A content of `None` means that the file does not exist.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct FileChange {
+    pub(crate) path: PathBuf,
+    // contents on disk, before any fix
+    pub(crate) old_contents: Option<String>,
+    // contents after the fixes of all checkers
+    pub(crate) new_contents: Option<String>,
+}
+
+impl FileChange {
+    /// A path outside the cwd has no diff path, as `git apply` does not accept it.
+    fn diff_path(&self) -> Option<String> {
+        let cwd = uri::base_dir().ok()?;
+        self.path
+            .strip_prefix(cwd)
+            .ok()
+            .map(|relative_path| relative_path.to_string_lossy().to_string())
+    }
+
+    fn to_diff(&self) -> String {
+        if self.old_contents == self.new_contents {
+            return "".to_string();
+        }
+        let Some(path) = self.diff_path() else {
+            return "".to_string();
+        };
+
+        let mut diff = format!("diff --git a/{path} b/{path}\n");
+        match (&self.old_contents, &self.new_contents) {
+            (None, _) => diff.push_str("new file mode 100644\n"),
+            (_, None) => diff.push_str(&format!("deleted file mode {}\n", git_mode(&self.path))),
+            _ => (),
+        }
+
+        let old_header = match self.old_contents {
+            Some(_) => format!("a/{path}"),
+            None => "/dev/null".to_string(),
+        };
+        let new_header = match self.new_contents {
+            Some(_) => format!("b/{path}"),
+            None => "/dev/null".to_string(),
+        };
+
+        diff.push_str(
+            &TextDiff::from_lines(
+                self.old_contents.as_deref().unwrap_or_default(),
+                self.new_contents.as_deref().unwrap_or_default(),
+            )
+            .unified_diff()
+            .header(&old_header, &new_header)
+            .to_string(),
+        );
+        diff
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn git_mode(path: &Path) -> &'static str {
+    use std::os::unix::fs::PermissionsExt;
+
+    match path.metadata() {
+        Ok(metadata) if metadata.permissions().mode() & 0o111 != 0 => "100755",
+        _ => "100644",
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn git_mode(_path: &Path) -> &'static str {
+    "100644"
+}
+
+fn files_in_dir(dir: &Path) -> Result<Vec<PathBuf>, CheckError> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let metadata = fs::symlink_metadata(&path)?;
+        if metadata.is_symlink() {
+            return Err(CheckError::String(format!(
+                "removing symlink {} can not be added to a patch",
+                path.to_string_lossy()
+            )));
+        } else if metadata.is_dir() {
+            files.extend(files_in_dir(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The fixes are not executed, but every checker sees the contents as changed by the checkers
+/// before it, so multiple checkers can change the same file.
+#[derive(Debug, Default)]
+pub(crate) struct Patch {
+    changes: Vec<FileChange>,
+}
+
+impl Patch {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn read_from_disk(path: &Path) -> Result<Option<String>, CheckError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(path)?))
+    }
+
+    pub(crate) fn contents(&self, path: &Path) -> Result<Option<String>, CheckError> {
+        match self.changes.iter().find(|change| change.path == path) {
+            Some(change) => Ok(change.new_contents.clone()),
+            None => Self::read_from_disk(path),
+        }
+    }
+
+    pub(crate) fn exists(&self, path: &Path) -> bool {
+        match self.changes.iter().find(|change| change.path == path) {
+            Some(change) => change.new_contents.is_some(),
+            None => path.exists(),
+        }
+    }
+
+    pub(crate) fn set_contents(
+        &mut self,
+        path: &Path,
+        contents: Option<String>,
+    ) -> Result<(), CheckError> {
+        match self.changes.iter_mut().find(|change| change.path == path) {
+            Some(change) => change.new_contents = contents,
+            None => self.changes.push(FileChange {
+                path: path.to_path_buf(),
+                old_contents: Self::read_from_disk(path)?,
+                new_contents: contents,
+            }),
+        }
+        Ok(())
+    }
+
+    /// A dir with a symlink can not be removed, as a patch can not contain the removal of a
+    /// symlink.
+    pub(crate) fn remove_dir(&mut self, dir: &Path) -> Result<(), CheckError> {
+        let mut paths = files_in_dir(dir)?;
+        paths.extend(
+            self.changes
+                .iter()
+                .filter(|change| change.path.starts_with(dir) && !paths.contains(&change.path))
+                .map(|change| change.path.clone())
+                .collect::<Vec<_>>(),
+        );
+        for path in paths {
+            self.set_contents(&path, None)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn changed_files(&self) -> Vec<&FileChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.old_contents != change.new_contents)
+            .collect()
+    }
+
+    pub(crate) fn patched_files(&self) -> Vec<&FileChange> {
+        self.changed_files()
+            .into_iter()
+            .filter(|change| change.diff_path().is_some())
+            .collect()
+    }
+
+    pub(crate) fn to_diff(&self) -> String {
+        for change in self.changed_files() {
+            if change.diff_path().is_none() {
+                log::warn!(
+                    "⚠ {} is outside the current dir, so its change is not part of the patch",
+                    change.path.to_string_lossy()
+                );
+            }
+        }
+        self.patched_files()
+            .iter()
+            .map(|change| change.to_diff())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch() {
+        let dir = tempfile::tempdir().unwrap();
+        let changed_file = dir.path().join("changed");
+        let removed_file = dir.path().join("removed");
+        let created_file = dir.path().join("created");
+        fs::write(&changed_file, "a\nb\n").unwrap();
+        fs::write(&removed_file, "c\n").unwrap();
+
+        let mut patch = Patch::new();
+        patch
+            .set_contents(&changed_file, Some("a\nb\nc\n".into()))
+            .unwrap();
+        // the second change of a file is based on the first change
+        assert_eq!(
+            patch.contents(&changed_file).unwrap(),
+            Some("a\nb\nc\n".into())
+        );
+        patch
+            .set_contents(&changed_file, Some("b\nc\n".into()))
+            .unwrap();
+        patch.set_contents(&removed_file, None).unwrap();
+        patch.set_contents(&created_file, Some("d".into())).unwrap();
+        assert!(!patch.exists(&removed_file));
+        assert!(patch.exists(&created_file));
+
+        // the files itself are not changed
+        assert_eq!(fs::read_to_string(&changed_file).unwrap(), "a\nb\n");
+        assert!(removed_file.exists());
+        assert!(!created_file.exists());
+
+        assert_eq!(
+            uri::with_base_dir(dir.path(), || patch.to_diff()),
+            "diff --git a/changed b/changed
+--- a/changed
++++ b/changed
+@@ -1,2 +1,2 @@
+-a
+ b
++c
+diff --git a/removed b/removed
+deleted file mode 100644
+--- a/removed
++++ /dev/null
+@@ -1 +0,0 @@
+-c
+diff --git a/created b/created
+new file mode 100644
+--- /dev/null
++++ b/created
+@@ -0,0 +1 @@
++d
+\\ No newline at end of file
+"
+        );
+    }
+
+    #[test]
+    fn test_patch_remove_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let removed_dir = dir.path().join("removed");
+        fs::create_dir_all(removed_dir.join("sub")).unwrap();
+        fs::write(removed_dir.join("a"), "a\n").unwrap();
+        fs::write(removed_dir.join("sub").join("b"), "b\n").unwrap();
+
+        let mut patch = Patch::new();
+        patch
+            .set_contents(&removed_dir.join("created"), Some("c\n".into()))
+            .unwrap();
+        patch.remove_dir(&removed_dir).unwrap();
+
+        assert!(!patch.exists(&removed_dir.join("created")));
+        assert_eq!(
+            patch
+                .changed_files()
+                .iter()
+                .map(|change| change.path.clone())
+                .collect::<Vec<_>>(),
+            vec![removed_dir.join("a"), removed_dir.join("sub").join("b")]
+        );
+        assert!(removed_dir.join("a").exists());
+    }
+
+    #[test]
+    fn test_patch_outside_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().join("cwd");
+        fs::create_dir(&cwd).unwrap();
+
+        let mut patch = Patch::new();
+        patch
+            .set_contents(&cwd.join("inside"), Some("a\n".into()))
+            .unwrap();
+        patch
+            .set_contents(&dir.path().join("outside"), Some("b\n".into()))
+            .unwrap();
+
+        uri::with_base_dir(&cwd, || {
+            assert_eq!(patch.changed_files().len(), 2);
+            assert_eq!(patch.patched_files().len(), 1);
+            assert!(!patch.to_diff().contains("outside"));
+        });
+    }
+
+    #[test]
+    fn test_patch_without_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        fs::write(&file, "a\n").unwrap();
+
+        let mut patch = Patch::new();
+        patch.set_contents(&file, Some("b\n".into())).unwrap();
+        patch.set_contents(&file, Some("a\n".into())).unwrap();
+
+        assert!(patch.changed_files().is_empty());
+        assert_eq!(patch.to_diff(), "");
+    }
+}
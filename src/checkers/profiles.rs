@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::checkers::{
+    base::CheckDefinitionError, get_option_string_from_check_table, read_string_array_from_table,
+};
+
+const PROFILE_KEYS: &[&str] = &["any_tags", "all_tags", "skip_tags", "inherits"];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct TagSelection {
+    pub(crate) any_tags: Vec<String>,
+    pub(crate) all_tags: Vec<String>,
+    pub(crate) skip_tags: Vec<String>,
+}
+
+impl TagSelection {
+    pub(crate) fn extend(&mut self, other: &TagSelection) {
+        for (tags, other_tags) in [
+            (&mut self.any_tags, &other.any_tags),
+            (&mut self.all_tags, &other.all_tags),
+            (&mut self.skip_tags, &other.skip_tags),
+        ] {
+            for tag in other_tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Profile {
+    // name of the profile of which the tags are added to the tags of this profile
+    pub(crate) inherits: Option<String>,
+    pub(crate) tags: TagSelection,
+}
+
+impl Profile {
+    pub(crate) fn from_table(table: &toml_edit::Table) -> Result<Self, CheckDefinitionError> {
+        if let Some((key, _)) = table.iter().find(|(key, _)| !PROFILE_KEYS.contains(key)) {
+            return Err(CheckDefinitionError::InvalidDefinition(format!(
+                "unknown key `{key}` for profile"
+            )));
+        }
+
+        Ok(Self {
+            inherits: get_option_string_from_check_table(table, "inherits")?,
+            tags: TagSelection {
+                any_tags: read_string_array_from_table(table, "any_tags")?,
+                all_tags: read_string_array_from_table(table, "all_tags")?,
+                skip_tags: read_string_array_from_table(table, "skip_tags")?,
+            },
+        })
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub(crate) enum ProfileError {
+    #[error("profile `{0}` is not defined")]
+    Unknown(String),
+    // the profiles from the first profile in the cycle up to and including that profile again
+    #[error("profiles inherit from each other: {}", .0.join(" → "))]
+    Cycle(Vec<String>),
+}
+
+pub(crate) fn resolve_profile(
+    profiles: &BTreeMap<String, Profile>,
+    name: &str,
+) -> Result<TagSelection, ProfileError> {
+    let mut tags = TagSelection::default();
+    let mut names: Vec<&str> = vec![];
+    let mut next = Some(name);
+    while let Some(name) = next {
+        if let Some(start) = names.iter().position(|&other| other == name) {
+            let mut cycle: Vec<String> = names[start..].iter().map(|n| n.to_string()).collect();
+            cycle.push(name.to_string());
+            return Err(ProfileError::Cycle(cycle));
+        }
+        let profile = profiles
+            .get(name)
+            .ok_or_else(|| ProfileError::Unknown(name.to_string()))?;
+        tags.extend(&profile.tags);
+        names.push(name);
+        next = profile.inherits.as_deref();
+    }
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn profiles(toml: &str) -> BTreeMap<String, Profile> {
+        let doc = toml_edit::DocumentMut::from_str(toml).unwrap();
+        doc["profiles"]
+            .as_table()
+            .unwrap()
+            .iter()
+            .map(|(name, table)| {
+                (
+                    name.to_string(),
+                    Profile::from_table(table.as_table().unwrap()).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_profile() {
+        let profiles = profiles(
+            r#"
+[profiles.base]
+skip_tags = ["slow"]
+
+[profiles.ci]
+inherits = "base"
+all_tags = ["linux"]
+skip_tags = ["slow", "desktop"]
+
+[profiles.loop]
+inherits = "loop"
+
+[profiles.entry]
+inherits = "a"
+
+[profiles.a]
+inherits = "b"
+
+[profiles.b]
+inherits = "c"
+
+[profiles.c]
+inherits = "a"
+
+[profiles.broken]
+inherits = "unknown"
+"#,
+        );
+
+        assert_eq!(
+            resolve_profile(&profiles, "ci"),
+            Ok(TagSelection {
+                any_tags: vec![],
+                all_tags: vec!["linux".into()],
+                skip_tags: vec!["slow".into(), "desktop".into()],
+            })
+        );
+        assert_eq!(
+            resolve_profile(&profiles, "loop"),
+            Err(ProfileError::Cycle(vec!["loop".into(), "loop".into()]))
+        );
+        assert_eq!(
+            resolve_profile(&profiles, "entry").unwrap_err().to_string(),
+            "profiles inherit from each other: a → b → c → a"
+        );
+        assert_eq!(
+            resolve_profile(&profiles, "broken"),
+            Err(ProfileError::Unknown("unknown".into()))
+        );
+    }
+
+    #[test]
+    fn test_profile_with_unknown_key() {
+        let mut table = toml_edit::Table::new();
+        table.insert("skip_tag", toml_edit::value(toml_edit::Array::new()));
+
+        assert_eq!(
+            Profile::from_table(&table),
+            Err(CheckDefinitionError::InvalidDefinition(
+                "unknown key `skip_tag` for profile".into()
+            ))
+        );
+    }
+}
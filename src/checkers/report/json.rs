@@ -0,0 +1,107 @@
+use serde_json::{Value, json};
+
+use crate::checkers::report::{CheckReport, RunReport};
+
+/// Version of the layout of the json document. Increase it when the layout changes in a
+/// backwards incompatible way.
+const REPORT_VERSION: u64 = 1;
+
+fn check_to_json(check: &CheckReport) -> Value {
+    let mut value = json!({
+        "checker_type": check.checker_type,
+        "checker_object": check.checker_object,
+        "file_with_checks": check.file_with_checks,
+        "tags": check.tags,
+        "result": check.result_str(),
+        "message": check.message(),
+    });
+    // only present for the checkers executed for a project of --each
+    if let Some(project) = &check.project {
+        value["project"] = json!(project);
+    }
+    value
+}
+
+pub(crate) fn to_json(report: &RunReport) -> Value {
+    let exit_status = report.exit_status();
+    json!({
+        "version": REPORT_VERSION,
+        "fix": report.fix,
+        "checks": report.checks.iter().map(check_to_json).collect::<Vec<_>>(),
+        "summary": {
+            "total": report.checks.len(),
+            "no_fix_needed": report.no_fix_needed_count(),
+            "fix_needed": report.fix_needed_count(),
+            "fix_executed": report.fix_executed_count(),
+            "error": report.error_count(),
+            "skipped": report.skipped_count(),
+        },
+        "exit_status": exit_status.to_string(),
+        "exit_code": exit_status.code(),
+    })
+}
+
+pub(crate) fn to_string(report: &RunReport) -> String {
+    serde_json::to_string_pretty(&to_json(report)).expect("report can be serialized")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checkers::base::CheckResult;
+
+    use super::*;
+
+    fn check_report(result: CheckResult) -> CheckReport {
+        CheckReport {
+            checker_type: "file_present".into(),
+            checker_object: "/tmp/file".into(),
+            file_with_checks: "check-config.toml".into(),
+            tags: vec!["tag".into()],
+            result,
+            project: None,
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut report = RunReport::new(false);
+        report.push(check_report(CheckResult::NoFixNeeded));
+        report.push(check_report(CheckResult::FixNeeded("create file".into())));
+
+        assert_eq!(
+            to_json(&report),
+            json!({
+                "version": 1,
+                "fix": false,
+                "checks": [
+                    {
+                        "checker_type": "file_present",
+                        "checker_object": "/tmp/file",
+                        "file_with_checks": "check-config.toml",
+                        "tags": ["tag"],
+                        "result": "no_fix_needed",
+                        "message": null,
+                    },
+                    {
+                        "checker_type": "file_present",
+                        "checker_object": "/tmp/file",
+                        "file_with_checks": "check-config.toml",
+                        "tags": ["tag"],
+                        "result": "fix_needed",
+                        "message": "create file",
+                    },
+                ],
+                "summary": {
+                    "total": 2,
+                    "no_fix_needed": 1,
+                    "fix_needed": 1,
+                    "fix_executed": 0,
+                    "error": 0,
+                    "skipped": 0,
+                },
+                "exit_status": "failure",
+                "exit_code": 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use crate::checkers::{
+    base::CheckResult,
+    report::{CheckReport, RunReport},
+};
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // control characters are not allowed in xml 1.0
+            c if c.is_control() && c != '\n' && c != '\r' && c != '\t' => (),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_testcase(xml: &mut String, check: &CheckReport) {
+    let _ = write!(
+        xml,
+        "    <testcase name=\"{} - {}\" classname=\"{}\"",
+        escape(&check.checker_type),
+        escape(&check.checker_object),
+        escape(&check.file_with_checks)
+    );
+    match &check.result {
+        CheckResult::NoFixNeeded => xml.push_str("/>\n"),
+        CheckResult::FixNeeded(action) => {
+            let _ = write!(
+                xml,
+                ">\n      <failure message=\"fix needed\" type=\"{}\">{}</failure>\n    </testcase>\n",
+                escape(&check.checker_type),
+                escape(action)
+            );
+        }
+        CheckResult::FixExecuted(action) => {
+            let _ = write!(
+                xml,
+                ">\n      <system-out>{}</system-out>\n    </testcase>\n",
+                escape(action)
+            );
+        }
+        CheckResult::Error(error) => {
+            let _ = write!(
+                xml,
+                ">\n      <error message=\"{}\" type=\"{}\"/>\n    </testcase>\n",
+                escape(error),
+                escape(&check.checker_type)
+            );
+        }
+        CheckResult::Skipped(reason) => {
+            let _ = write!(
+                xml,
+                ">\n      <skipped message=\"{}\"/>\n    </testcase>\n",
+                escape(reason)
+            );
+        }
+    }
+}
+
+fn suite_name(check: &CheckReport) -> String {
+    match &check.project {
+        Some(project) => format!("{project}: {}", check.file_with_checks),
+        None => check.file_with_checks.clone(),
+    }
+}
+
+pub(crate) fn to_string(report: &RunReport) -> String {
+    // group by the file where the checkers are defined, in order of appearance
+    let mut suites: Vec<(String, Vec<&CheckReport>)> = vec![];
+    for check in report.checks.iter() {
+        let name = suite_name(check);
+        match suites.iter_mut().find(|(suite, _)| *suite == name) {
+            Some((_, checks)) => checks.push(check),
+            None => suites.push((name, vec![check])),
+        }
+    }
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"check-config\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">",
+        report.checks.len(),
+        report.fix_needed_count(),
+        report.error_count(),
+        report.skipped_count()
+    );
+
+    for (file_with_checks, checks) in suites {
+        let failures = checks
+            .iter()
+            .filter(|check| matches!(check.result, CheckResult::FixNeeded(_)))
+            .count();
+        let errors = checks
+            .iter()
+            .filter(|check| matches!(check.result, CheckResult::Error(_)))
+            .count();
+        let skipped = checks
+            .iter()
+            .filter(|check| matches!(check.result, CheckResult::Skipped(_)))
+            .count();
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">",
+            escape(&file_with_checks),
+            checks.len(),
+            failures,
+            errors,
+            skipped
+        );
+        for check in checks {
+            write_testcase(&mut xml, check);
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_report(file_with_checks: &str, result: CheckResult) -> CheckReport {
+        CheckReport {
+            checker_type: "lines_present".into(),
+            checker_object: "/tmp/.bashrc".into(),
+            file_with_checks: file_with_checks.into(),
+            tags: vec![],
+            result,
+            project: None,
+        }
+    }
+
+    #[test]
+    fn test_to_string() {
+        let mut report = RunReport::new(false);
+        report.push(check_report("a.toml", CheckResult::NoFixNeeded));
+        report.push(check_report(
+            "b.toml",
+            CheckResult::FixNeeded("+export A=\"<1>\"".into()),
+        ));
+        report.push(check_report("a.toml", CheckResult::Error("failed".into())));
+        report.push(check_report(
+            "b.toml",
+            CheckResult::Skipped("dependency `a` failed".into()),
+        ));
+
+        assert_eq!(
+            to_string(&report),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="check-config" tests="4" failures="1" errors="1" skipped="1">
+  <testsuite name="a.toml" tests="2" failures="0" errors="1" skipped="0">
+    <testcase name="lines_present - /tmp/.bashrc" classname="a.toml"/>
+    <testcase name="lines_present - /tmp/.bashrc" classname="a.toml">
+      <error message="failed" type="lines_present"/>
+    </testcase>
+  </testsuite>
+  <testsuite name="b.toml" tests="2" failures="1" errors="0" skipped="1">
+    <testcase name="lines_present - /tmp/.bashrc" classname="b.toml">
+      <failure message="fix needed" type="lines_present">+export A=&quot;&lt;1&gt;&quot;</failure>
+    </testcase>
+    <testcase name="lines_present - /tmp/.bashrc" classname="b.toml">
+      <skipped message="dependency `a` failed"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
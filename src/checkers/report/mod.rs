@@ -0,0 +1,160 @@
+use crate::{checkers::base::CheckResult, cli::ExitStatus};
+
+pub(crate) mod json;
+pub(crate) mod junit;
+pub(crate) mod sarif;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CheckReport {
+    pub(crate) checker_type: String,
+    pub(crate) checker_object: String,
+    // path to the file where the checker is defined, relative to the cwd when possible
+    pub(crate) file_with_checks: String,
+    pub(crate) tags: Vec<String>,
+    pub(crate) result: CheckResult,
+    // dir of the project the checker is executed for with --each, relative to the cwd
+    pub(crate) project: Option<String>,
+}
+
+impl CheckReport {
+    pub(crate) fn result_str(&self) -> &'static str {
+        match self.result {
+            CheckResult::NoFixNeeded => "no_fix_needed",
+            CheckResult::FixNeeded(_) => "fix_needed",
+            CheckResult::FixExecuted(_) => "fix_executed",
+            CheckResult::Error(_) => "error",
+            CheckResult::Skipped(_) => "skipped",
+        }
+    }
+
+    pub(crate) fn message(&self) -> Option<&str> {
+        match &self.result {
+            CheckResult::NoFixNeeded => None,
+            CheckResult::FixNeeded(message)
+            | CheckResult::FixExecuted(message)
+            | CheckResult::Error(message)
+            | CheckResult::Skipped(message) => Some(message.as_str()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct RunReport {
+    pub(crate) fix: bool,
+    pub(crate) checks: Vec<CheckReport>,
+    // problems in the definitions of the checkers, of which the checkers are not executed
+    pub(crate) definition_issues: usize,
+}
+
+impl RunReport {
+    pub(crate) fn new(fix: bool) -> Self {
+        Self {
+            fix,
+            checks: vec![],
+            definition_issues: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, check_report: CheckReport) {
+        self.checks.push(check_report);
+    }
+
+    pub(crate) fn extend_with_project(&mut self, project: &str, report: RunReport) {
+        self.checks
+            .extend(report.checks.into_iter().map(|check| CheckReport {
+                project: Some(project.to_string()),
+                ..check
+            }));
+        self.definition_issues += report.definition_issues;
+    }
+
+    pub(crate) fn no_fix_needed_count(&self) -> usize {
+        self.count(|result| matches!(result, CheckResult::NoFixNeeded))
+    }
+
+    pub(crate) fn fix_needed_count(&self) -> usize {
+        self.count(|result| matches!(result, CheckResult::FixNeeded(_)))
+    }
+
+    pub(crate) fn fix_executed_count(&self) -> usize {
+        self.count(|result| matches!(result, CheckResult::FixExecuted(_)))
+    }
+
+    pub(crate) fn error_count(&self) -> usize {
+        self.count(|result| matches!(result, CheckResult::Error(_)))
+    }
+
+    pub(crate) fn skipped_count(&self) -> usize {
+        self.count(|result| matches!(result, CheckResult::Skipped(_)))
+    }
+
+    fn count(&self, predicate: impl Fn(&CheckResult) -> bool) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| predicate(&check.result))
+            .count()
+    }
+
+    pub(crate) fn exit_status(&self) -> ExitStatus {
+        if self.error_count() > 0 || self.definition_issues > 0 {
+            ExitStatus::Error
+        } else if self.fix_needed_count() > 0 {
+            ExitStatus::Failure
+        } else {
+            ExitStatus::Success
+        }
+    }
+
+    pub(crate) fn log_project_summary(&self, project: &str) {
+        let icon = match self.exit_status() {
+            ExitStatus::Success => "🥇",
+            ExitStatus::Failure => "🪛",
+            ExitStatus::Error => "🚨",
+        };
+        log::error!(
+            "{icon} {project}: {} checks, {} violations to fix, {} errors",
+            self.checks.len(),
+            self.fix_needed_count(),
+            self.error_count()
+        );
+    }
+
+    pub(crate) fn log_summary(&self) {
+        log::warn!("⬜ {checks} checks found", checks = self.checks.len());
+        if self.fix {
+            log::warn!("✅ {} checks fixed", self.fix_executed_count());
+            log::warn!(
+                "✅ {} checks did not need a fix",
+                self.no_fix_needed_count()
+            );
+        }
+
+        match self.fix_needed_count() {
+            0 => log::error!("🥇 No violations found."),
+            1 => log::error!("🪛 There is 1 violation to fix.",),
+            fix_needed_count => {
+                log::error!("🪛 There are {fix_needed_count} violations to fix.",)
+            }
+        }
+
+        match self.error_count() {
+            0 => (),
+            1 => log::error!("🚨 There was 1 error executing a fix.",),
+            error_count => log::error!("🚨 There are {error_count} errors executing a fix.",),
+        }
+
+        match self.definition_issues {
+            0 => (),
+            1 => log::error!("🚨 There is 1 problem in the checkers, so it is not executed."),
+            issue_count => log::error!(
+                "🚨 There are {issue_count} problems in the checkers, so they are not executed."
+            ),
+        }
+
+        match self.skipped_count() {
+            0 => (),
+            1 => log::warn!("⏭️ 1 check is skipped."),
+            skipped_count => log::warn!("⏭️ {skipped_count} checks are skipped."),
+        }
+    }
+}
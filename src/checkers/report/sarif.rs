@@ -0,0 +1,194 @@
+use std::path::Path;
+
+use serde_json::{Value, json};
+use url::Url;
+
+use crate::{
+    checkers::{
+        base::CheckResult,
+        report::{CheckReport, RunReport},
+    },
+    uri,
+};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SRCROOT: &str = "SRCROOT";
+
+fn srcroot() -> Option<Url> {
+    Url::from_directory_path(uri::base_dir().ok()?).ok()
+}
+
+/// Paths inside the cwd are relative to `SRCROOT`, so code scanning tools can link them to the
+/// files in the repository.
+fn artifact_location(checker_object: &str) -> Value {
+    let Ok(url) = Url::from_file_path(Path::new(checker_object)) else {
+        // not a path, like the name of a package
+        return json!({"uri": checker_object});
+    };
+    if let Some(srcroot) = srcroot()
+        && url.path().starts_with(srcroot.path())
+        && let Some(relative_uri) = srcroot.make_relative(&url)
+    {
+        return json!({"uri": relative_uri, "uriBaseId": SRCROOT});
+    }
+    json!({"uri": url.as_str()})
+}
+
+fn properties(check: &CheckReport) -> Value {
+    let mut properties = json!({
+        "file_with_checks": check.file_with_checks,
+        "tags": check.tags,
+    });
+    if let Some(project) = &check.project {
+        properties["project"] = json!(project);
+    }
+    properties
+}
+
+fn check_to_result(check: &CheckReport) -> Option<Value> {
+    let (level, message) = match &check.result {
+        CheckResult::NoFixNeeded | CheckResult::Skipped(_) => return None,
+        CheckResult::FixNeeded(action) => ("error", action.as_str()),
+        CheckResult::Error(error) => ("error", error.as_str()),
+        CheckResult::FixExecuted(action) => ("note", action.as_str()),
+    };
+
+    Some(json!({
+        "ruleId": check.checker_type,
+        "level": level,
+        "message": {
+            "text": format!("{} ({}): {}", check.checker_type, check.result_str(), message),
+        },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": artifact_location(&check.checker_object),
+            },
+        }],
+        "properties": properties(check),
+    }))
+}
+
+pub(crate) fn to_json(report: &RunReport) -> Value {
+    let mut rule_ids: Vec<&str> = vec![];
+    for check in report.checks.iter() {
+        if !rule_ids.contains(&check.checker_type.as_str()) {
+            rule_ids.push(check.checker_type.as_str());
+        }
+    }
+
+    let mut run = json!({
+            "tool": {
+                "driver": {
+                    "name": "check-config",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rule_ids
+                        .iter()
+                        .map(|rule_id| json!({"id": rule_id, "name": rule_id}))
+                        .collect::<Vec<_>>(),
+                },
+            },
+            "results": report.checks.iter().filter_map(check_to_result).collect::<Vec<_>>(),
+    });
+    if let Some(srcroot) = srcroot() {
+        run["originalUriBaseIds"] = json!({SRCROOT: {"uri": srcroot.as_str()}});
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [run],
+    })
+}
+
+pub(crate) fn to_string(report: &RunReport) -> String {
+    serde_json::to_string_pretty(&to_json(report)).expect("report can be serialized")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let check_report = |checker_type: &str, checker_object: String, result| CheckReport {
+            checker_type: checker_type.into(),
+            checker_object,
+            file_with_checks: "check-config.toml".into(),
+            tags: vec!["tag".into()],
+            result,
+            project: None,
+        };
+        let mut report = RunReport::new(false);
+        report.push(check_report(
+            "file_present",
+            "/tmp/file".into(),
+            CheckResult::NoFixNeeded,
+        ));
+        report.push(check_report(
+            "lines_absent",
+            dir.path()
+                .join("sub dir")
+                .join("file")
+                .to_string_lossy()
+                .into(),
+            CheckResult::FixNeeded("remove lines".into()),
+        ));
+        report.push(check_report(
+            "lines_absent",
+            "/other/file".into(),
+            CheckResult::FixNeeded("remove lines".into()),
+        ));
+        report.push(check_report(
+            "package_present",
+            "ruff".into(),
+            CheckResult::Error("not installed".into()),
+        ));
+
+        let sarif = uri::with_base_dir(dir.path(), || to_json(&report));
+
+        assert_eq!(
+            sarif["runs"][0]["tool"]["driver"]["rules"],
+            json!([
+                {"id": "file_present", "name": "file_present"},
+                {"id": "lines_absent", "name": "lines_absent"},
+                {"id": "package_present", "name": "package_present"},
+            ])
+        );
+        assert_eq!(
+            sarif["runs"][0]["originalUriBaseIds"],
+            json!({"SRCROOT": {"uri": Url::from_directory_path(dir.path()).unwrap().as_str()}})
+        );
+        assert_eq!(
+            sarif["runs"][0]["results"][0],
+            json!({
+                "ruleId": "lines_absent",
+                "level": "error",
+                "message": {"text": "lines_absent (fix_needed): remove lines"},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {"uri": "sub%20dir/file", "uriBaseId": "SRCROOT"},
+                    },
+                }],
+                "properties": {"file_with_checks": "check-config.toml", "tags": ["tag"]},
+            })
+        );
+        let artifact_uris = sarif["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| {
+                result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"].clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            artifact_uris,
+            vec![
+                json!("sub%20dir/file"),
+                json!("file:///other/file"),
+                json!("ruff")
+            ]
+        );
+    }
+}
@@ -0,0 +1,634 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    checkers::{variables::stringify, when::Condition},
+    uri::{ReadPath, ReadablePath},
+};
+
+/// The maximum depth of nested includes, which stops a template which includes itself
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+struct Tag {
+    keyword: String,
+    argument: String,
+    line: usize,
+}
+
+enum Segment {
+    Text(String),
+    /// The contents of `{{ }}` or `${}`
+    Expression(String, usize),
+    Tag(Tag),
+}
+
+enum Filter {
+    Upper,
+    Lower,
+    Quote,
+    Indent(usize),
+    Default(String),
+}
+
+struct Expression {
+    name: String,
+    filters: Vec<Filter>,
+}
+
+enum Node {
+    Text(String),
+    Output {
+        expression: Expression,
+        line: usize,
+    },
+    If {
+        branches: Vec<(Condition, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        item: String,
+        list: String,
+        body: Vec<Node>,
+        line: usize,
+    },
+    Include {
+        path: String,
+        line: usize,
+    },
+}
+
+fn at(line: usize, error: impl std::fmt::Display) -> String {
+    format!("line {line}: {error}")
+}
+
+fn undefined(name: &str) -> String {
+    format!("variable `{name}` is not defined")
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// A tag on a line of its own is removed together with its indentation and line ending, so it
+/// does not leave an empty line.
+fn split(template: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = vec![];
+    let mut text = String::new();
+    let mut position = 0;
+
+    while position < template.len() {
+        let rest = &template[position..];
+        if let Some(escaped) = ["\\${", "\\{{", "\\{%"]
+            .into_iter()
+            .find(|escaped| rest.starts_with(escaped))
+        {
+            text.push_str(&escaped[1..]);
+            position += escaped.len();
+            continue;
+        }
+        let close = match rest.get(..2) {
+            Some("${") => "}",
+            Some("{{") => "}}",
+            Some("{%") => "%}",
+            _ => {
+                let c = rest.chars().next().expect("rest is not empty");
+                text.push(c);
+                position += c.len_utf8();
+                continue;
+            }
+        };
+
+        let line = template[..position].matches('\n').count() + 1;
+        let length = rest[2..].find(close).ok_or_else(|| {
+            at(
+                line,
+                format!("`{}` is not closed with `{close}`", &rest[..2]),
+            )
+        })?;
+        let contents = rest[2..2 + length].trim();
+        let mut end = position + 2 + length + close.len();
+
+        if close != "%}" {
+            segments.push(Segment::Text(std::mem::take(&mut text)));
+            segments.push(Segment::Expression(contents.to_string(), line));
+            position = end;
+            continue;
+        }
+
+        let line_start = template[..position].rfind('\n').map_or(0, |i| i + 1);
+        let line_ending =
+            ["\r\n", "\n", ""]
+                .into_iter()
+                .find(|line_ending| match line_ending.is_empty() {
+                    true => end == template.len(),
+                    false => template[end..].starts_with(line_ending),
+                });
+        if let Some(line_ending) = line_ending
+            && template[line_start..position].trim().is_empty()
+        {
+            text.truncate(text.len() - (position - line_start));
+            end += line_ending.len();
+        }
+
+        let (keyword, argument) = contents
+            .split_once(char::is_whitespace)
+            .unwrap_or((contents, ""));
+        segments.push(Segment::Text(std::mem::take(&mut text)));
+        segments.push(Segment::Tag(Tag {
+            keyword: keyword.to_string(),
+            argument: argument.trim().to_string(),
+            line,
+        }));
+        position = end;
+    }
+    segments.push(Segment::Text(text));
+
+    Ok(segments)
+}
+
+fn parse_string(literal: &str) -> Option<String> {
+    ['"', '\''].into_iter().find_map(|quote| {
+        literal
+            .strip_prefix(quote)
+            .and_then(|literal| literal.strip_suffix(quote))
+            .filter(|literal| !literal.contains(quote))
+            .map(str::to_string)
+    })
+}
+
+fn parse_filter(filter: &str) -> Result<Filter, String> {
+    let (name, argument) = match filter.split_once('(') {
+        Some((name, argument)) => match argument.strip_suffix(')') {
+            Some(argument) => (name.trim(), Some(argument.trim())),
+            None => return Err(format!("filter `{filter}` is not closed with `)`")),
+        },
+        None => (filter, None),
+    };
+
+    match (name, argument) {
+        ("upper", None) => Ok(Filter::Upper),
+        ("lower", None) => Ok(Filter::Lower),
+        ("quote", None) => Ok(Filter::Quote),
+        ("indent", None) => Ok(Filter::Indent(4)),
+        ("indent", Some(width)) => width
+            .parse()
+            .map(Filter::Indent)
+            .map_err(|_| format!("width of indent ({width}) is not a number")),
+        // numbers and booleans may be given without quotes
+        ("default", Some(value)) if !value.is_empty() => Ok(Filter::Default(
+            parse_string(value).unwrap_or_else(|| value.to_string()),
+        )),
+        ("default", _) => Err("default expects a value".to_string()),
+        ("upper" | "lower" | "quote", Some(_)) => {
+            Err(format!("filter `{name}` does not expect arguments"))
+        }
+        _ => Err(format!("unknown filter `{name}`")),
+    }
+}
+
+fn parse_expression(expression: &str) -> Result<Expression, String> {
+    // split on the pipes which are not within quotes
+    let mut parts = vec![String::new()];
+    let mut quote = None;
+    for c in expression.chars() {
+        match (c, quote) {
+            ('|', None) => parts.push(String::new()),
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            _ => (),
+        }
+        if c != '|' || quote.is_some() {
+            parts.last_mut().expect("parts is not empty").push(c);
+        }
+    }
+
+    let name = parts[0].trim().to_string();
+    if !is_identifier(&name) {
+        return Err(format!("`{expression}` is not a variable name"));
+    }
+    let filters = parts[1..]
+        .iter()
+        .map(|filter| parse_filter(filter.trim()))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Expression { name, filters })
+}
+
+fn parse_condition(tag: &Tag) -> Result<Condition, String> {
+    Condition::parse(&tag.argument).map_err(|e| {
+        at(
+            tag.line,
+            format!("condition `{}` is invalid: {e}", tag.argument),
+        )
+    })
+}
+
+fn parse_if(tag: &Tag, segments: &mut impl Iterator<Item = Segment>) -> Result<Node, String> {
+    let not_closed = || at(tag.line, "`if` is not closed with `endif`");
+    let mut branches = vec![];
+    let mut condition = parse_condition(tag)?;
+    loop {
+        let (nodes, end) = parse_nodes(segments, &["elif", "else", "endif"])?;
+        let end = end.ok_or_else(not_closed)?;
+        branches.push((condition, nodes));
+        match end.keyword.as_str() {
+            "elif" => condition = parse_condition(&end)?,
+            "else" => {
+                let (otherwise, end) = parse_nodes(segments, &["endif"])?;
+                end.ok_or_else(not_closed)?;
+                return Ok(Node::If {
+                    branches,
+                    otherwise,
+                });
+            }
+            _ => {
+                return Ok(Node::If {
+                    branches,
+                    otherwise: vec![],
+                });
+            }
+        }
+    }
+}
+
+fn parse_for(tag: &Tag, segments: &mut impl Iterator<Item = Segment>) -> Result<Node, String> {
+    let (item, list) = match tag.argument.split_whitespace().collect::<Vec<_>>()[..] {
+        [item, "in", list] if is_identifier(item) && is_identifier(list) => (item, list),
+        _ => {
+            return Err(at(
+                tag.line,
+                format!(
+                    "`for {}` is not formatted as `for <item> in <list>`",
+                    tag.argument
+                ),
+            ));
+        }
+    };
+    let (body, end) = parse_nodes(segments, &["endfor"])?;
+    end.ok_or_else(|| at(tag.line, "`for` is not closed with `endfor`"))?;
+
+    Ok(Node::For {
+        item: item.to_string(),
+        list: list.to_string(),
+        body,
+        line: tag.line,
+    })
+}
+
+fn parse_nodes(
+    segments: &mut impl Iterator<Item = Segment>,
+    ends: &[&str],
+) -> Result<(Vec<Node>, Option<Tag>), String> {
+    let mut nodes = vec![];
+    while let Some(segment) = segments.next() {
+        match segment {
+            Segment::Text(text) => nodes.push(Node::Text(text)),
+            Segment::Expression(expression, line) => nodes.push(Node::Output {
+                expression: parse_expression(&expression).map_err(|e| at(line, e))?,
+                line,
+            }),
+            Segment::Tag(tag) if ends.contains(&tag.keyword.as_str()) => {
+                return Ok((nodes, Some(tag)));
+            }
+            Segment::Tag(tag) => match tag.keyword.as_str() {
+                "if" => nodes.push(parse_if(&tag, segments)?),
+                "for" => nodes.push(parse_for(&tag, segments)?),
+                "include" => match parse_string(&tag.argument) {
+                    Some(path) => nodes.push(Node::Include {
+                        path,
+                        line: tag.line,
+                    }),
+                    None => return Err(at(tag.line, "include expects a quoted path")),
+                },
+                keyword => return Err(at(tag.line, format!("unexpected `{keyword}`"))),
+            },
+        }
+    }
+    Ok((nodes, None))
+}
+
+impl Expression {
+    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<String, String> {
+        let mut value = variables.get(&self.name).cloned();
+        for filter in &self.filters {
+            if let Filter::Default(default) = filter {
+                value = value.or_else(|| Some(default.clone()));
+                continue;
+            }
+            let current = value.ok_or_else(|| undefined(&self.name))?;
+            value = Some(match filter {
+                Filter::Upper => current.to_uppercase(),
+                Filter::Lower => current.to_lowercase(),
+                Filter::Quote => {
+                    format!("\"{}\"", current.replace('\\', "\\\\").replace('"', "\\\""))
+                }
+                // like jinja, the first line and empty lines are not indented
+                Filter::Indent(width) => current
+                    .split('\n')
+                    .enumerate()
+                    .map(|(index, line)| match index == 0 || line.trim().is_empty() {
+                        true => line.to_string(),
+                        false => format!("{}{line}", " ".repeat(*width)),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                Filter::Default(_) => unreachable!("default is handled above"),
+            });
+        }
+        value.ok_or_else(|| undefined(&self.name))
+    }
+}
+
+/// A relative path is relative to the checkers file.
+fn include_path(path: &str, file_with_checks: &ReadablePath) -> Result<ReadablePath, String> {
+    if url::Url::parse(path).is_ok() || path.starts_with('/') || path.starts_with('~') {
+        ReadablePath::from_string(path, Some(file_with_checks))
+            .map_err(|_| format!("{path} is not a valid path"))
+    } else {
+        Ok(file_with_checks.join(path))
+    }
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    variables: &HashMap<String, String>,
+    file_with_checks: &ReadablePath,
+    depth: usize,
+    output: &mut String,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Output { expression, line } => {
+                output.push_str(&expression.evaluate(variables).map_err(|e| at(*line, e))?)
+            }
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                let nodes = branches
+                    .iter()
+                    .find(|(condition, _)| condition.evaluate(variables))
+                    .map_or(otherwise, |(_, nodes)| nodes);
+                render_nodes(nodes, variables, file_with_checks, depth, output)?;
+            }
+            Node::For {
+                item,
+                list,
+                body,
+                line,
+            } => {
+                let value = variables
+                    .get(list)
+                    .ok_or_else(|| at(*line, undefined(list)))?;
+                let Ok(Value::Array(values)) = serde_json::from_str(value) else {
+                    return Err(at(*line, format!("variable `{list}` is not an array")));
+                };
+                let mut variables = variables.clone();
+                for value in values {
+                    variables.insert(item.clone(), stringify(&value));
+                    render_nodes(body, &variables, file_with_checks, depth, output)?;
+                }
+            }
+            Node::Include { path, line } => {
+                if depth >= MAX_INCLUDE_DEPTH {
+                    return Err(at(
+                        *line,
+                        format!("includes are nested more than {MAX_INCLUDE_DEPTH} times"),
+                    ));
+                }
+                let template = include_path(path, file_with_checks)
+                    .and_then(|include| {
+                        include
+                            .read_to_string()
+                            .map_err(|e| format!("{path} can not be read ({e})"))
+                    })
+                    .map_err(|e| at(*line, e))?;
+                let nodes = parse(&template).map_err(|e| at(*line, format!("{path}: {e}")))?;
+                render_nodes(&nodes, variables, file_with_checks, depth + 1, output)
+                    .map_err(|e| at(*line, format!("{path}: {e}")))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, String> {
+    let mut segments = split(template)?.into_iter();
+    match parse_nodes(&mut segments, &[])? {
+        (nodes, None) => Ok(nodes),
+        (_, Some(tag)) => Err(at(tag.line, format!("unexpected `{}`", tag.keyword))),
+    }
+}
+
+pub(crate) fn render(
+    template: &str,
+    variables: &HashMap<String, String>,
+    file_with_checks: &ReadablePath,
+) -> Result<String, String> {
+    let mut output = String::new();
+    render_nodes(
+        &parse(template)?,
+        variables,
+        file_with_checks,
+        0,
+        &mut output,
+    )?;
+    Ok(output)
+}
+
+/// Replace `${name}` by the value of the variable. Any other text, like `${name}` of a variable
+/// which is not defined, `{{ }}` or `{% %}`, is kept as is, so a literal value, like
+/// `${workspaceFolder}` of vscode or `${{ secrets.TOKEN }}` of github, does not need escaping.
+/// `\${` is kept as a literal `${`.
+pub(crate) fn substitute(text: &str, variables: &HashMap<String, String>) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('\\') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        output.push_str(&rest[..start]);
+        let value = rest[start + 2..]
+            .find('}')
+            .map(|end| &rest[start + 2..start + 2 + end])
+            .filter(|name| is_identifier(name))
+            .and_then(|name| Some((name, variables.get(name)?)));
+        match value {
+            Some((name, value)) => {
+                output.push_str(value);
+                rest = &rest[start + 2 + name.len() + 1..];
+            }
+            None => {
+                output.push_str("${");
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+pub(crate) fn substitute_value(value: &mut toml_edit::Value, variables: &HashMap<String, String>) {
+    match value {
+        toml_edit::Value::String(string) => {
+            *string = toml_edit::Formatted::new(substitute(string.value(), variables));
+        }
+        toml_edit::Value::Array(array) => {
+            for value in array.iter_mut() {
+                substitute_value(value, variables);
+            }
+        }
+        toml_edit::Value::InlineTable(table) => {
+            for (_, value) in table.iter_mut() {
+                substitute_value(value, variables);
+            }
+        }
+        _ => (),
+    }
+}
+
+pub(crate) fn substitute_item(item: &mut toml_edit::Item, variables: &HashMap<String, String>) {
+    match item {
+        toml_edit::Item::Value(value) => substitute_value(value, variables),
+        toml_edit::Item::Table(table) => table
+            .iter_mut()
+            .for_each(|(_, item)| substitute_item(item, variables)),
+        toml_edit::Item::ArrayOfTables(tables) => tables.iter_mut().for_each(|table| {
+            table
+                .iter_mut()
+                .for_each(|(_, item)| substitute_item(item, variables))
+        }),
+        toml_edit::Item::None => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_with(dir: &std::path::Path, template: &str) -> Result<String, String> {
+        let variables = HashMap::from([
+            ("name".to_string(), "world".to_string()),
+            ("shell".to_string(), "zsh".to_string()),
+            ("paths".to_string(), r#"["/bin","/usr/bin"]"#.to_string()),
+            ("lines".to_string(), "a\nb\n\nc".to_string()),
+        ]);
+        let file_with_checks = ReadablePath::from_string(
+            &format!("file://{}/check-config.toml", dir.to_string_lossy()),
+            None,
+        )
+        .unwrap();
+        render(template, &variables, &file_with_checks)
+    }
+
+    #[test]
+    fn test_substitute() {
+        let variables = HashMap::from([
+            ("project".to_string(), "app".to_string()),
+            ("cc.home".to_string(), "/home/me".to_string()),
+        ]);
+
+        assert_eq!(
+            substitute("${cc.home}/${project}/a.toml", &variables),
+            "/home/me/app/a.toml"
+        );
+        // literal values are kept as is
+        for literal in [
+            "${workspaceFolder}/.venv/bin/python",
+            "${{ secrets.TOKEN }}",
+            "out/{{weird}}.txt",
+            "{% raw %}",
+            "${project",
+            "$project",
+        ] {
+            assert_eq!(substitute(literal, &variables), literal);
+        }
+        assert_eq!(
+            substitute("\\${project}/${project}", &variables),
+            "${project}/app"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("header.tmpl"), "# {{ name | upper }}\n").unwrap();
+        let render = |template| render_with(dir.path(), template).unwrap();
+
+        assert_eq!(render(r#"Hello ${name} \${name}!"#), "Hello world ${name}!");
+        assert_eq!(
+            render(r#"{{name}} \{{ name }} {a}"#),
+            "world {{ name }} {a}"
+        );
+        assert_eq!(
+            render(r#"{{ unknown | default("a|b") | upper }} {{ name | quote }}"#),
+            r#"A|B "world""#
+        );
+        assert_eq!(render("  - {{ lines | indent(2) }}"), "  - a\n  b\n\n  c");
+        assert_eq!(
+            render(
+                "{% if var.shell == \"bash\" %}\nbashrc\n{% elif var.shell == \"zsh\" %}\n  \
+                 zshrc\n{% else %}\nprofile\n{% endif %}\nend"
+            ),
+            "  zshrc\nend"
+        );
+        assert_eq!(render("{% if not var.unknown %}unset{% endif %}"), "unset");
+        assert_eq!(
+            render("PATH=\n{% for path in paths %}\n  {{ path }}:\n{% endfor %}\n"),
+            "PATH=\n  /bin:\n  /usr/bin:\n"
+        );
+        assert_eq!(
+            render("{% include \"header.tmpl\" %}\nbody\n"),
+            "# WORLD\nbody\n"
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("loop.tmpl"), "{% include \"loop.tmpl\" %}").unwrap();
+
+        for (template, error) in [
+            ("a\n${unknown}", "line 2: variable `unknown` is not defined"),
+            ("{{ name | upper", "line 1: `{{` is not closed with `}}`"),
+            ("{{ name | title }}", "line 1: unknown filter `title`"),
+            ("{{ a b }}", "line 1: `a b` is not a variable name"),
+            (
+                "{% if var.name %}",
+                "line 1: `if` is not closed with `endif`",
+            ),
+            (
+                "{% if name %}{% endif %}",
+                "line 1: condition `name` is invalid: unknown property `name`",
+            ),
+            ("{% endfor %}", "line 1: unexpected `endfor`"),
+            (
+                "{% for path of paths %}",
+                "line 1: `for path of paths` is not formatted as `for <item> in <list>`",
+            ),
+            (
+                "{% for c in name %}{% endfor %}",
+                "line 1: variable `name` is not an array",
+            ),
+            (
+                "{% include \"unknown.tmpl\" %}",
+                "line 1: unknown.tmpl can not be read",
+            ),
+            (
+                "{% include \"loop.tmpl\" %}",
+                "includes are nested more than 16 times",
+            ),
+        ] {
+            let result = render_with(dir.path(), template).unwrap_err();
+            assert!(result.contains(error), "{template}: {result}");
+        }
+    }
+}
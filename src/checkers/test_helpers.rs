@@ -19,7 +19,11 @@ pub(crate) fn get_generic_check() -> GenericChecker {
         .expect("valid path"),
         tags: Vec::new(),
         check_only: true,
+        id: None,
+        depends_on: Vec::new(),
+        when: None,
         variables: HashMap::new(),
+        location: None,
     }
 }
 
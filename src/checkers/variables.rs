@@ -0,0 +1,475 @@
+use std::{collections::HashMap, path::Path, process::Command, sync::OnceLock};
+
+use serde_json::Value;
+
+use crate::{
+    mapping::generic::Value as _,
+    uri::{self, ReadPath, ReadablePath},
+};
+
+const SOURCE_KEYS: &[&str] = &["command", "file", "key", "format"];
+
+// the variables given by --var, which are not overwritten by the checkers files
+static OVERRIDES: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+pub(crate) fn configure_overrides(overrides: HashMap<String, String>) {
+    if OVERRIDES.set(overrides).is_err() {
+        log::debug!("the variables of --var are already configured");
+    }
+}
+
+// whether the commands of variables are executed, which they are not when the checkers are
+// only validated or listed
+static RUN_COMMANDS: OnceLock<bool> = OnceLock::new();
+
+pub(crate) fn configure_commands(run_commands: bool) {
+    if RUN_COMMANDS.set(run_commands).is_err() {
+        log::debug!("the execution of commands of variables is already configured");
+    }
+}
+
+pub(crate) fn is_overridden(name: &str) -> bool {
+    OVERRIDES
+        .get()
+        .is_some_and(|overrides| overrides.contains_key(name))
+}
+
+pub(crate) fn is_reserved(name: &str) -> bool {
+    name == "cc" || name.starts_with("cc.")
+}
+
+pub(crate) fn parse_assignment(assignment: &str) -> Result<(String, String), String> {
+    match assignment.split_once('=') {
+        Some((key, _)) if is_reserved(key.trim()) => Err(format!(
+            "variable `{}` is reserved for the built-in variables",
+            key.trim()
+        )),
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!(
+            "variable `{assignment}` is not formatted as KEY=VALUE"
+        )),
+    }
+}
+
+fn hostname() -> Option<String> {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .or_else(|| run_command("hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+fn git_root_and_branch(cwd: &Path) -> (Option<String>, Option<String>) {
+    let Ok(repo) = git2::Repository::discover(cwd) else {
+        return (None, None);
+    };
+    let root = repo.workdir().map(|root| {
+        root.to_string_lossy()
+            .trim_end_matches(std::path::MAIN_SEPARATOR)
+            .to_string()
+    });
+    let branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string));
+    (root, branch)
+}
+
+fn check_file_dir(file_with_checks: &ReadablePath) -> Option<String> {
+    match file_with_checks.as_ref().to_file_path() {
+        Ok(path) => path
+            .parent()
+            .map(|parent| parent.to_string_lossy().to_string()),
+        Err(_) => Some(file_with_checks.join("./").as_ref().to_string()),
+    }
+}
+
+/// `cc.check_file_dir` differs for included files, so it is set for every checkers file
+pub(crate) fn set_check_file_dir(
+    variables: &mut HashMap<String, String>,
+    file_with_checks: &ReadablePath,
+) {
+    match check_file_dir(file_with_checks) {
+        Some(dir) => variables.insert("cc.check_file_dir".to_string(), dir),
+        None => variables.remove("cc.check_file_dir"),
+    };
+}
+
+/// A variable which can not be determined, like `cc.git_branch` outside a git repo, is not set.
+pub(crate) fn builtin_variables(file_with_checks: &ReadablePath) -> HashMap<String, String> {
+    let cwd = uri::base_dir().ok();
+    let home = dirs::home_dir();
+    let (git_root, git_branch) = match &cwd {
+        Some(cwd) => git_root_and_branch(cwd),
+        None => (None, None),
+    };
+    let path_string = |path: std::path::PathBuf| path.to_string_lossy().to_string();
+
+    [
+        ("os", Some(std::env::consts::OS.to_string())),
+        ("arch", Some(std::env::consts::ARCH.to_string())),
+        ("hostname", hostname()),
+        (
+            "user",
+            ["USER", "USERNAME"]
+                .into_iter()
+                .find_map(|name| std::env::var(name).ok()),
+        ),
+        ("home", home.clone().map(path_string)),
+        (
+            "xdg_config_home",
+            std::env::var_os("XDG_CONFIG_HOME")
+                .map(std::path::PathBuf::from)
+                .filter(|path| path.is_absolute())
+                .or_else(|| home.map(|home| home.join(".config")))
+                .map(path_string),
+        ),
+        ("cwd", cwd.map(path_string)),
+        ("git_root", git_root),
+        ("git_branch", git_branch),
+        ("check_file_dir", check_file_dir(file_with_checks)),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some((format!("cc.{name}"), value?)))
+    .collect()
+}
+
+pub(crate) fn stringify(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+pub(crate) fn read_variable(
+    item: &toml_edit::Item,
+    file_with_checks: &ReadablePath,
+) -> Result<String, String> {
+    match item.as_table_like() {
+        Some(source) => read_source(source, file_with_checks),
+        None => Ok(stringify(&Value::from_toml_value(item))),
+    }
+}
+
+fn read_source(
+    source: &dyn toml_edit::TableLike,
+    file_with_checks: &ReadablePath,
+) -> Result<String, String> {
+    if let Some((key, _)) = source.iter().find(|(key, _)| !SOURCE_KEYS.contains(key)) {
+        return Err(format!("unknown key `{key}`"));
+    }
+    let string = |key: &str| match source.get(key) {
+        None => Ok(None),
+        Some(item) => item
+            .as_str()
+            .map(|value| Some(value.to_string()))
+            .ok_or_else(|| format!("`{key}` is not a string")),
+    };
+
+    match (string("command")?, string("file")?) {
+        // a remote checkers file, like one of an https or git include, must not be able to
+        // execute commands on this machine
+        (Some(command), None) if file_with_checks.as_ref().scheme() != "file" => Err(format!(
+            "command `{command}` is only allowed in local checkers files"
+        )),
+        (Some(command), None) if !RUN_COMMANDS.get().copied().unwrap_or(true) => {
+            log::debug!("command `{command}` is not executed");
+            Ok(format!("<output of `{command}`>"))
+        }
+        (Some(command), None) => run_command(&command),
+        (None, Some(file)) => {
+            let key = string("key")?.ok_or("`key` is missing")?;
+            read_file(&file, &key, string("format")?.as_deref(), file_with_checks)
+        }
+        _ => Err("either `command` or `file` must be given".into()),
+    }
+}
+
+/// The command is executed by the shell in the base dir of the run
+fn run_command(command: &str) -> Result<String, String> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    if let Ok(dir) = uri::base_dir() {
+        shell.current_dir(dir);
+    }
+    let output = shell
+        .output()
+        .map_err(|e| format!("command `{command}` can not be executed ({e})"))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "command `{command}` failed ({})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn file_format<'a>(file: &'a str, format: Option<&'a str>) -> Result<&'a str, String> {
+    let format = match format {
+        Some(format) => format,
+        None => {
+            let path = Path::new(file);
+            match path.extension().and_then(|extension| extension.to_str()) {
+                _ if path.file_name().is_some_and(|name| name == ".env") => "dotenv",
+                Some("env") => "dotenv",
+                Some("yml") => "yaml",
+                Some(extension) => extension,
+                None => "",
+            }
+        }
+    };
+    match format {
+        "dotenv" | "toml" | "json" | "yaml" => Ok(format),
+        _ => Err(format!(
+            "format of {file} is unknown, use `format` with dotenv, toml, json or yaml"
+        )),
+    }
+}
+
+fn parse_dotenv(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line
+                .strip_prefix("export ")
+                .unwrap_or(line)
+                .split_once('=')?;
+            let value = value.trim();
+            let value = ['"', '\'']
+                .into_iter()
+                .find_map(|quote| {
+                    value
+                        .strip_prefix(quote)
+                        .and_then(|value| value.strip_suffix(quote))
+                })
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn lookup<'a>(value: &'a Value, key_path: &str) -> Option<&'a Value> {
+    key_path
+        .split('.')
+        .try_fold(value, |value, key| match value {
+            Value::Array(array) => array.get(key.parse::<usize>().ok()?),
+            value => value.get(key),
+        })
+}
+
+/// The value of the key in the file. A `config:` path is relative to the checkers file, another
+/// relative path to the cwd, or to the project dir with --each.
+fn read_file(
+    file: &str,
+    key: &str,
+    format: Option<&str>,
+    file_with_checks: &ReadablePath,
+) -> Result<String, String> {
+    let format = file_format(file, format)?;
+    let contents = ReadablePath::from_string(file, Some(file_with_checks))
+        .map_err(|_| format!("{file} is not a valid path"))?
+        .read_to_string()
+        .map_err(|e| format!("{file} can not be read ({e})"))?;
+
+    let document: Value = match format {
+        "dotenv" => {
+            return parse_dotenv(&contents)
+                .remove(key)
+                .ok_or_else(|| format!("{key} is not found in {file}"));
+        }
+        "toml" => {
+            let document = contents
+                .parse::<toml_edit::DocumentMut>()
+                .map_err(|e| format!("{file} is not valid toml ({})", e.message()))?;
+            Value::from_toml_value(document.as_item())
+        }
+        "json" => serde_json::from_str(&contents)
+            .map_err(|e| format!("{file} is not valid json ({e})"))?,
+        _ => serde_yaml_ng::from_str(&contents)
+            .map_err(|e| format!("{file} is not valid yaml ({e})"))?,
+    };
+    lookup(&document, key)
+        .map(stringify)
+        .ok_or_else(|| format!("{key} is not found in {file}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn read_variables(dir: &Path, variables_toml: &str) -> Vec<(String, Result<String, String>)> {
+        let file_with_checks = ReadablePath::from_string(
+            &format!("file://{}/check-config.toml", dir.to_string_lossy()),
+            None,
+        )
+        .unwrap();
+        let doc = toml_edit::DocumentMut::from_str(variables_toml).unwrap();
+        doc["variables"]
+            .as_table()
+            .unwrap()
+            .iter()
+            .map(|(name, item)| (name.to_string(), read_variable(item, &file_with_checks)))
+            .collect()
+    }
+
+    #[test]
+    fn test_typed_variables() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(
+            read_variables(
+                dir.path(),
+                r#"
+[variables]
+string = "text"
+integer = 3
+float = 1.5
+boolean = true
+array = ["a", 1]
+"#
+            ),
+            vec![
+                ("string".into(), Ok("text".into())),
+                ("integer".into(), Ok("3".into())),
+                ("float".into(), Ok("1.5".into())),
+                ("boolean".into(), Ok("true".into())),
+                ("array".into(), Ok(r#"["a",1]"#.into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variables_from_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".env"),
+            "# comment\nexport USER_NAME=\"Jane Doe\"\nEMPTY=\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"app\"\nkeywords = [\"a\", \"b\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"engines": {"node": ">=20"}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("config.yml"), "images:\n  - name: base\n").unwrap();
+
+        assert_eq!(
+            read_variables(
+                dir.path(),
+                r#"
+[variables]
+user = { file = "config:.env", key = "USER_NAME" }
+empty = { file = "config:.env", key = "EMPTY" }
+name = { file = "config:pyproject.toml", key = "project.name" }
+keywords = { file = "config:pyproject.toml", key = "project.keywords" }
+node = { file = "config:package.json", key = "engines.node" }
+image = { file = "config:config.yml", key = "images.0.name" }
+echo = { command = "echo hello" }
+missing = { file = "config:pyproject.toml", key = "project.version" }
+failing = { command = "exit 1" }
+both = { command = "echo", file = "config:.env" }
+unknown_format = { file = "config:settings.conf", key = "a" }
+"#
+            ),
+            vec![
+                ("user".into(), Ok("Jane Doe".into())),
+                ("empty".into(), Ok("".into())),
+                ("name".into(), Ok("app".into())),
+                ("keywords".into(), Ok(r#"["a","b"]"#.into())),
+                ("node".into(), Ok(">=20".into())),
+                ("image".into(), Ok("base".into())),
+                ("echo".into(), Ok("hello".into())),
+                (
+                    "missing".into(),
+                    Err("project.version is not found in config:pyproject.toml".into())
+                ),
+                ("failing".into(), Err("command `exit 1` failed ()".into())),
+                (
+                    "both".into(),
+                    Err("either `command` or `file` must be given".into())
+                ),
+                (
+                    "unknown_format".into(),
+                    Err(
+                        "format of config:settings.conf is unknown, use `format` with \
+                         dotenv, toml, json or yaml"
+                            .into()
+                    )
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_in_remote_checkers_file() {
+        let file_with_checks =
+            ReadablePath::from_string("https://example.com/check-config.toml", None).unwrap();
+        let doc = toml_edit::DocumentMut::from_str(
+            "[variables]\nuser = { command = \"git config user.email\" }\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_variable(&doc["variables"]["user"], &file_with_checks),
+            Err("command `git config user.email` is only allowed in local checkers files".into())
+        );
+    }
+
+    #[test]
+    fn test_builtin_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_with_checks = ReadablePath::from_string(
+            &format!("file://{}/check-config.toml", dir.path().to_string_lossy()),
+            None,
+        )
+        .unwrap();
+        let variables = builtin_variables(&file_with_checks);
+
+        assert_eq!(variables["cc.os"], std::env::consts::OS);
+        assert_eq!(variables["cc.arch"], std::env::consts::ARCH);
+        assert_eq!(variables["cc.check_file_dir"], dir.path().to_string_lossy());
+        assert_eq!(
+            variables["cc.cwd"],
+            std::env::current_dir().unwrap().to_string_lossy()
+        );
+        assert!(variables.keys().all(|name| is_reserved(name)));
+    }
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            parse_assignment("name = a=b"),
+            Ok(("name".into(), " a=b".into()))
+        );
+        assert_eq!(
+            parse_assignment("cc.os=linux"),
+            Err("variable `cc.os` is reserved for the built-in variables".into())
+        );
+        assert_eq!(
+            parse_assignment("=value"),
+            Err("variable `=value` is not formatted as KEY=VALUE".into())
+        );
+    }
+}
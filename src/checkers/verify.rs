@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use crate::checkers::{
+    RelativeUrl,
+    base::{CheckResult, Checker},
+};
+
+fn file_state(path: &Path) -> Option<u64> {
+    let contents = std::fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+fn checker_label(check: &dyn Checker) -> String {
+    format!(
+        "{} - {} - {}",
+        check.generic_checker().file_with_checks.short_url_str(),
+        check.checker_type(),
+        check.checker_object()
+    )
+}
+
+// hash of the contents of a file and the checker which caused it
+type FileState = (Option<u64>, Option<String>);
+
+/// Verifies the executed fixes of a run. A checker must pass after its fix is executed, and a
+/// fix must not bring a file back in the state before the fix of another checker, as the
+/// checkers would fight each other on every run.
+#[derive(Debug, Default)]
+pub(crate) struct FixVerifier {
+    // all states of the touched files during the run, with the checker which caused the state.
+    // The first state is the state before the first fix which touched the file.
+    history: HashMap<PathBuf, Vec<FileState>>,
+}
+
+impl FixVerifier {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn before_fix(&mut self, check: &dyn Checker) {
+        for path in check.touched_paths() {
+            let state = file_state(&path);
+            self.history
+                .entry(path)
+                .or_insert_with(|| vec![(state, None)]);
+        }
+    }
+
+    pub(crate) fn verify(&mut self, check: &dyn Checker, result: CheckResult) -> CheckResult {
+        if !matches!(result, CheckResult::FixExecuted(_)) {
+            return result;
+        }
+
+        match check.check_(false) {
+            Ok(CheckResult::FixNeeded(action)) => {
+                return CheckResult::Error(format!(
+                    "fix is not idempotent, a fix is still needed after executing it: {action}"
+                ));
+            }
+            Err(e) => {
+                return CheckResult::Error(format!("check after executing the fix failed ({e})"));
+            }
+            Ok(_) => (),
+        }
+
+        let label = checker_label(check);
+        let mut reverted_checkers: Vec<(PathBuf, Vec<String>)> = vec![];
+        for path in check.touched_paths() {
+            let state = file_state(&path);
+            let history = self.history.entry(path.clone()).or_default();
+            if history.last().map(|(last_state, _)| *last_state) == Some(state) {
+                continue;
+            }
+
+            // back in an earlier state, so the fixes after that state are reverted
+            if let Some(position) = history
+                .iter()
+                .position(|(earlier_state, _)| *earlier_state == state)
+            {
+                let reverted = history[position + 1..]
+                    .iter()
+                    .filter_map(|(_, checker)| checker.clone())
+                    .filter(|checker| *checker != label)
+                    .collect::<Vec<_>>();
+                if !reverted.is_empty() {
+                    reverted_checkers.push((path, reverted));
+                }
+            }
+            history.push((state, Some(label.clone())));
+        }
+
+        match reverted_checkers.first() {
+            None => result,
+            Some((path, reverted)) => CheckResult::Error(format!(
+                "contents of {} flip-flop, the fix reverts the fix of: {}",
+                path.to_string_lossy(),
+                reverted.join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checkers::{
+        GenericChecker,
+        base::{CheckConstructor, CheckError},
+        file::{lines_absent::LinesAbsent, lines_present::LinesPresent},
+        test_helpers,
+    };
+
+    use super::*;
+
+    fn fix(verifier: &mut FixVerifier, check: &dyn Checker) -> CheckResult {
+        verifier.before_fix(check);
+        let result = check.check(true);
+        verifier.verify(check, result)
+    }
+
+    fn check_table(file: &Path, lines: &str) -> toml_edit::Table {
+        let mut check_table = toml_edit::Table::new();
+        check_table.insert("file", file.to_string_lossy().to_string().into());
+        check_table.insert("lines", lines.into());
+        check_table
+    }
+
+    // checker which never passes
+    #[derive(Debug)]
+    struct NeverPasses(GenericChecker);
+
+    impl Checker for NeverPasses {
+        fn checker_type(&self) -> String {
+            "never_passes".into()
+        }
+        fn generic_checker(&self) -> &GenericChecker {
+            &self.0
+        }
+        fn checker_object(&self) -> String {
+            "object".into()
+        }
+        fn check_(&self, fix: bool) -> Result<CheckResult, CheckError> {
+            match fix {
+                true => Ok(CheckResult::FixExecuted("fix".into())),
+                false => Ok(CheckResult::FixNeeded("fix".into())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_not_idempotent() {
+        let check = NeverPasses(test_helpers::get_generic_check());
+
+        assert_eq!(
+            fix(&mut FixVerifier::new(), &check),
+            CheckResult::Error(
+                "fix is not idempotent, a fix is still needed after executing it: fix".into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_flip_flop() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        std::fs::write(&file, "a\n").unwrap();
+        let generic_check = GenericChecker {
+            check_only: false,
+            ..test_helpers::get_generic_check()
+        };
+
+        let lines_present =
+            LinesPresent::from_check_table(generic_check.clone(), check_table(&file, "b\n"))
+                .unwrap();
+        let lines_absent =
+            LinesAbsent::from_check_table(generic_check, check_table(&file, "b\n")).unwrap();
+
+        let mut verifier = FixVerifier::new();
+        assert!(matches!(
+            fix(&mut verifier, &lines_present),
+            CheckResult::FixExecuted(_)
+        ));
+        let CheckResult::Error(error) = fix(&mut verifier, &lines_absent) else {
+            panic!("flip-flop is not detected");
+        };
+        assert!(error.starts_with(&format!(
+            "contents of {} flip-flop, the fix reverts the fix of: ",
+            file.to_string_lossy()
+        )));
+        assert!(error.ends_with(&format!("lines_present - {}", file.to_string_lossy())));
+    }
+}
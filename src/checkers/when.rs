@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+
+use crate::{checkers::base::CheckDefinitionError, uri::WritablePath};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Condition {
+    Property {
+        property: Property,
+        value: Option<String>,
+        negated: bool,
+    },
+    Exists(String),
+    Command(String),
+    Bool(bool),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Property {
+    Os,
+    Arch,
+    Family,
+    Env(String),
+    Variable(String),
+}
+
+impl Property {
+    fn value(&self, variables: &HashMap<String, String>) -> Option<String> {
+        match self {
+            Property::Os => Some(std::env::consts::OS.to_string()),
+            Property::Arch => Some(std::env::consts::ARCH.to_string()),
+            Property::Family => Some(std::env::consts::FAMILY.to_string()),
+            Property::Env(name) => std::env::var(name).ok(),
+            Property::Variable(name) => variables.get(name).cloned(),
+        }
+    }
+}
+
+fn command_is_available(command: &str) -> bool {
+    let Some(paths) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&paths).any(|dir| {
+        let path = dir.join(command);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            path.metadata().is_ok_and(|metadata| {
+                metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            path.is_file() || path.with_extension("exe").is_file()
+        }
+    })
+}
+
+impl Condition {
+    pub(crate) fn parse(expression: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?,
+            position: 0,
+        };
+        let condition = parser.parse_or()?;
+        match parser.next() {
+            None => Ok(condition),
+            Some(token) => Err(format!("unexpected {token}")),
+        }
+    }
+
+    pub(crate) fn evaluate(&self, variables: &HashMap<String, String>) -> bool {
+        match self {
+            Condition::Property {
+                property,
+                value,
+                negated,
+            } => {
+                let matched = match (property.value(variables), value) {
+                    (Some(actual), Some(value)) => actual == *value,
+                    (Some(actual), None) => !actual.is_empty(),
+                    (None, _) => false,
+                };
+                matched != *negated
+            }
+            Condition::Exists(path) => {
+                WritablePath::from_string(path).is_ok_and(|path| path.exists())
+            }
+            Condition::Command(command) => command_is_available(command),
+            Condition::Bool(value) => *value,
+            Condition::Not(condition) => !condition.evaluate(variables),
+            Condition::And(left, right) => left.evaluate(variables) && right.evaluate(variables),
+            Condition::Or(left, right) => left.evaluate(variables) || right.evaluate(variables),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct When {
+    pub(crate) expression: String,
+    pub(crate) condition: Condition,
+}
+
+impl When {
+    pub(crate) fn parse(expression: &str) -> Result<Self, CheckDefinitionError> {
+        let condition = Condition::parse(expression).map_err(|e| invalid_when(expression, e))?;
+        Ok(Self {
+            expression: expression.to_string(),
+            condition,
+        })
+    }
+
+    pub(crate) fn evaluate(&self, variables: &HashMap<String, String>) -> bool {
+        self.condition.evaluate(variables)
+    }
+}
+
+fn invalid_when(expression: &str, error: String) -> CheckDefinitionError {
+    CheckDefinitionError::InvalidDefinition(format!("when `{expression}` is invalid: {error}"))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Equal,
+    NotEqual,
+    OpenParenthesis,
+    CloseParenthesis,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Identifier(identifier) => write!(f, "`{identifier}`"),
+            Token::String(string) => write!(f, "\"{string}\""),
+            Token::Equal => write!(f, "`==`"),
+            Token::NotEqual => write!(f, "`!=`"),
+            Token::OpenParenthesis => write!(f, "`(`"),
+            Token::CloseParenthesis => write!(f, "`)`"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '(' => tokens.push(Token::OpenParenthesis),
+            ')' => tokens.push(Token::CloseParenthesis),
+            '=' | '!' => match chars.next() {
+                Some('=') if c == '=' => tokens.push(Token::Equal),
+                Some('=') => tokens.push(Token::NotEqual),
+                _ => return Err(format!("`{c}` must be followed by `=`")),
+            },
+            '"' | '\'' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => string.push(next),
+                        None => return Err("string is not closed".to_string()),
+                    }
+                }
+                tokens.push(Token::String(string));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut identifier = c.to_string();
+                while let Some(next) = chars.peek()
+                    && (next.is_alphanumeric() || *next == '_' || *next == '.' || *next == '-')
+                {
+                    identifier.push(*next);
+                    chars.next();
+                }
+                tokens.push(Token::Identifier(identifier));
+            }
+            c => return Err(format!("unexpected character `{c}`")),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Identifier(identifier)) if identifier == keyword)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("expected {expected}, found {token}")),
+            None => Err(format!("expected {expected}, found end of expression")),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Condition, String> {
+        let mut condition = self.parse_and()?;
+        while self.next_is_keyword("or") {
+            self.next();
+            condition = Condition::Or(Box::new(condition), Box::new(self.parse_and()?));
+        }
+        Ok(condition)
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut condition = self.parse_not()?;
+        while self.next_is_keyword("and") {
+            self.next();
+            condition = Condition::And(Box::new(condition), Box::new(self.parse_not()?));
+        }
+        Ok(condition)
+    }
+
+    fn parse_not(&mut self) -> Result<Condition, String> {
+        if self.next_is_keyword("not") {
+            self.next();
+            return Ok(Condition::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Condition, String> {
+        let identifier = match self.next() {
+            Some(Token::OpenParenthesis) => {
+                let condition = self.parse_or()?;
+                self.expect(Token::CloseParenthesis)?;
+                return Ok(condition);
+            }
+            Some(Token::Identifier(identifier)) => identifier,
+            Some(token) => return Err(format!("unexpected {token}")),
+            None => return Err("unexpected end of expression".to_string()),
+        };
+
+        match identifier.as_str() {
+            "true" => return Ok(Condition::Bool(true)),
+            "false" => return Ok(Condition::Bool(false)),
+            "exists" | "command" => {
+                self.expect(Token::OpenParenthesis)?;
+                let argument = match self.next() {
+                    Some(Token::String(argument)) => argument,
+                    _ => return Err(format!("{identifier}() expects a string")),
+                };
+                self.expect(Token::CloseParenthesis)?;
+                return Ok(match identifier.as_str() {
+                    "exists" => Condition::Exists(argument),
+                    _ => Condition::Command(argument),
+                });
+            }
+            _ => (),
+        }
+
+        let property = match identifier.split_once('.') {
+            None if identifier == "os" => Property::Os,
+            None if identifier == "arch" => Property::Arch,
+            None if identifier == "family" => Property::Family,
+            Some(("env", name)) if !name.is_empty() => Property::Env(name.to_string()),
+            Some(("var", name)) if !name.is_empty() => Property::Variable(name.to_string()),
+            _ => return Err(format!("unknown property `{identifier}`")),
+        };
+
+        let negated = match self.peek() {
+            Some(Token::Equal) => false,
+            Some(Token::NotEqual) => true,
+            // env.<name> and var.<name> can be used without a value, to check they are set
+            _ if matches!(property, Property::Env(_) | Property::Variable(_)) => {
+                return Ok(Condition::Property {
+                    property,
+                    value: None,
+                    negated: false,
+                });
+            }
+            _ => return Err(format!("`{identifier}` must be compared with == or !=")),
+        };
+        self.next();
+        let value = match self.next() {
+            Some(Token::String(value)) => value,
+            _ => return Err(format!("`{identifier}` must be compared with a string")),
+        };
+
+        Ok(Condition::Property {
+            property,
+            value: Some(value),
+            negated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checkers::{
+        GenericChecker,
+        base::{CheckConstructor, CheckResult, Checker},
+        file::file_present::FilePresent,
+        test_helpers,
+    };
+
+    use super::*;
+
+    fn evaluate(expression: &str) -> bool {
+        let variables = HashMap::from([("name".to_string(), "value".to_string())]);
+        When::parse(expression).unwrap().evaluate(&variables)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        assert!(evaluate(&format!("os == \"{}\"", std::env::consts::OS)));
+        assert!(evaluate(&format!("arch == '{}'", std::env::consts::ARCH)));
+        assert!(evaluate("os != \"plan9\""));
+        assert!(evaluate("var.name and var.name == \"value\""));
+        assert!(!evaluate("var.unknown or var.name != \"value\""));
+        assert!(evaluate("not env.CHECK_CONFIG_UNKNOWN_VARIABLE"));
+        assert!(evaluate("env.PATH"));
+        assert!(evaluate("exists(\"/\") and not exists(\"/unknown/path\")"));
+        assert!(evaluate(
+            "command(\"sh\") and not command(\"unknown-command\")"
+        ));
+        assert!(evaluate("false or (true and not false)"));
+        // and binds stronger than or
+        assert!(evaluate("true or false and false"));
+        assert!(!evaluate("not true or false"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (expression, error) in [
+            ("os", "`os` must be compared with == or !="),
+            ("os == linux", "`os` must be compared with a string"),
+            ("os = \"linux\"", "`=` must be followed by `=`"),
+            ("hostname == \"a\"", "unknown property `hostname`"),
+            ("(true", "expected `)`, found end of expression"),
+            ("true false", "unexpected `false`"),
+            ("exists(1)", "exists() expects a string"),
+            ("os == \"linux", "string is not closed"),
+            ("", "unexpected end of expression"),
+        ] {
+            assert_eq!(
+                When::parse(expression),
+                Err(CheckDefinitionError::InvalidDefinition(format!(
+                    "when `{expression}` is invalid: {error}"
+                ))),
+                "{expression}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        let mut check_table = toml_edit::Table::new();
+        check_table.insert("file", file.to_string_lossy().to_string().into());
+        let generic_check = GenericChecker {
+            check_only: false,
+            when: Some(When::parse("os == \"plan9\"").unwrap()),
+            ..test_helpers::get_generic_check()
+        };
+        let check = FilePresent::from_check_table(generic_check, check_table).unwrap();
+
+        assert_eq!(
+            check.check(true),
+            CheckResult::Skipped("condition `os == \"plan9\"` is not met".into())
+        );
+        assert!(!file.exists());
+    }
+}
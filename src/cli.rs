@@ -1,12 +1,28 @@
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
-use std::{collections::HashMap, io::Write};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+};
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 
-use crate::checkers::{RelativeUrl, base::Checker};
-use crate::uri::ReadablePath;
+use crate::checkers::RelativeUrl;
+use crate::checkers::base::{CheckResult, Checker};
+use crate::checkers::dependencies::FailedDependencies;
+use crate::checkers::patch::Patch;
+use crate::checkers::profiles::{Profile, TagSelection, resolve_profile};
+use crate::checkers::report::{self, RunReport};
+use crate::checkers::variables;
+use crate::checkers::verify::FixVerifier;
+use crate::glob;
+use crate::http;
+use crate::journal::{self, Journal, JournalError};
+use crate::python;
+use crate::uri::{self, ReadablePath, WritablePath};
 
-use super::checkers::read_checks_from_path;
+use super::checkers::{load_checks_from_path, read_checks_from_path};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ExitStatus {
@@ -18,16 +34,44 @@ pub enum ExitStatus {
     Error,
 }
 
+impl ExitStatus {
+    pub fn code(self) -> u8 {
+        match self {
+            ExitStatus::Success => 0,
+            ExitStatus::Failure => 1,
+            ExitStatus::Error => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExitStatus::Success => write!(f, "success"),
+            ExitStatus::Failure => write!(f, "failure"),
+            ExitStatus::Error => write!(f, "error"),
+        }
+    }
+}
+
 impl From<ExitStatus> for ExitCode {
     fn from(status: ExitStatus) -> Self {
-        match status {
-            ExitStatus::Success => ExitCode::from(0),
-            ExitStatus::Failure => ExitCode::from(1),
-            ExitStatus::Error => ExitCode::from(2),
-        }
+        ExitCode::from(status.code())
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// Human readable log lines
+    Text,
+    /// One json document with all the results, printed to stdout
+    Json,
+    /// JUnit XML with a testsuite per check file, printed to stdout
+    Junit,
+    /// SARIF log for code scanning tools, printed to stdout
+    Sarif,
+}
+
 /// Config Checker will check and optional fix your config files based on checkers defined in a toml file.
 /// It can check toml, yaml, json and plain text files.
 #[derive(Parser)]
@@ -37,13 +81,33 @@ struct Cli {
     /// Defaults (in order of precedence):
     /// - check-config.toml
     /// - pyproject.toml with a tool.check-config key
-    #[arg(short, long, env = "CHECK_CONFIG_PATH", verbatim_doc_comment)]
+    #[arg(
+        short,
+        long,
+        env = "CHECK_CONFIG_PATH",
+        verbatim_doc_comment,
+        global = true
+    )]
     path: Option<String>,
 
     /// Try to fix the config
     #[arg(long, default_value = "false")]
     fix: bool,
 
+    /// Ask for confirmation before fixing a checker. Only used in combination with --fix.
+    #[arg(short, long, default_value = "false", requires = "fix")]
+    interactive: bool,
+
+    /// Write the changes the fixes would make to files as one patch, which can be applied
+    /// with `git apply`. Fixes are not executed, unless --fix is used as well.
+    #[arg(long, value_name = "PATH")]
+    emit_patch: Option<PathBuf>,
+
+    /// Execute the checkers for every project dir which matches the glob, like `services/*`.
+    /// Relative paths of the checkers are resolved from the project dir instead of the cwd.
+    #[arg(long, value_name = "GLOB", conflicts_with = "emit_patch")]
+    each: Option<String>,
+
     /// List all checks. Checks are not executed.
     #[arg(short, long, default_value = "false")]
     list_checkers: bool,
@@ -52,6 +116,11 @@ struct Cli {
     #[arg(long = "env", default_value = "false")]
     use_env_variables: bool,
 
+    /// Set a variable for templates, formatted as KEY=VALUE. It is not overwritten by the
+    /// variables of the checkers file.
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = variables::parse_assignment)]
+    vars: Vec<(String, String)>,
+
     /// Execute the checkers with one of the specified tags
     #[arg(long, value_delimiter = ',', env = "CHECK_CONFIG_ANY_TAGS")]
     any_tags: Vec<String>,
@@ -64,14 +133,121 @@ struct Cli {
     #[arg(long, value_delimiter = ',', env = "CHECK_CONFIG_SKIP_TAGS")]
     skip_tags: Vec<String>,
 
+    /// Select the checkers with the tags of the profile defined in the checkers file. The
+    /// tags given by --any-tags, --all-tags and --skip-tags are added to them.
+    #[arg(long, env = "CHECK_CONFIG_PROFILE")]
+    profile: Option<String>,
+
+    /// List all profiles with their tags. Checks are not executed.
+    #[arg(long, default_value = "false")]
+    list_profiles: bool,
+
     /// Create missing directories
     #[arg(short, long, default_value = "false", env = "CHECK_CONFIG_CREATE_DIRS")]
     create_missing_directories: bool,
 
+    /// Do not fetch remote includes and sources, but use the cached copies. A remote file
+    /// which is not cached results in an error.
+    #[arg(long, default_value = "false", env = "CHECK_CONFIG_OFFLINE")]
+    offline: bool,
+
+    /// Number of seconds a cached copy of a remote file is used without asking the server
+    /// whether it is changed
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value = "0",
+        env = "CHECK_CONFIG_CACHE_TTL"
+    )]
+    cache_ttl: u64,
+
+    /// Header which is added to the requests of remote files, formatted as `NAME: VALUE`, or as
+    /// `HOST=NAME: VALUE` to add it only to the requests to that host
+    #[arg(
+        long = "http-header",
+        value_name = "[HOST=]NAME: VALUE",
+        value_parser = http::ExtraHeader::parse
+    )]
+    http_headers: Vec<http::ExtraHeader>,
+
+    /// Number of seconds to wait for the connection to the server of a remote file
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value = "10",
+        env = "CHECK_CONFIG_CONNECT_TIMEOUT"
+    )]
+    connect_timeout: u64,
+
+    /// Number of seconds which a request to the server of a remote file may take in total,
+    /// including the reading of the response
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value = "30",
+        env = "CHECK_CONFIG_READ_TIMEOUT"
+    )]
+    read_timeout: u64,
+
+    /// Number of times the request of a remote file is repeated, with an increasing delay, after
+    /// a server error or a connection error
+    #[arg(long, default_value = "2", env = "CHECK_CONFIG_RETRIES")]
+    retries: u32,
+
+    /// Url of the proxy for the requests of remote files. When it is not given, the proxy of the
+    /// HTTP_PROXY, HTTPS_PROXY and NO_PROXY environment variables is used.
+    #[arg(long, value_name = "URL", env = "CHECK_CONFIG_PROXY")]
+    proxy: Option<String>,
+
+    /// Comma separated hosts which are not requested via the proxy given by --proxy
+    #[arg(long, value_name = "HOSTS", env = "CHECK_CONFIG_NO_PROXY")]
+    no_proxy: Option<String>,
+
+    /// File with PEM certificates which are trusted in addition to the system certificates
+    #[arg(long, value_name = "PATH", env = "CHECK_CONFIG_CA_BUNDLE")]
+    ca_bundle: Option<PathBuf>,
+
+    /// Python interpreter which resolves the packages of py:// paths. By default the python of
+    /// the active virtualenv, of a .venv dir, of uv, or else python3 or python is used.
+    #[arg(long, value_name = "PATH", env = "CHECK_CONFIG_PYTHON")]
+    python: Option<PathBuf>,
+
+    /// Format of the report of the executed checkers
+    #[arg(
+        long,
+        value_enum,
+        default_value = "text",
+        env = "CHECK_CONFIG_OUTPUT_FORMAT"
+    )]
+    output_format: OutputFormat,
+
     // -v s
     // -vv show all
     #[clap(flatten)]
     verbose: clap_verbosity_flag::Verbosity,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Validate the checkers file and all its includes. No checks are executed and no files
+    /// to check are read.
+    Validate,
+    /// Restore the files and directories changed by the last fix run, or by the given run
+    Undo {
+        /// Id of the run to undo
+        run: Option<String>,
+
+        /// List the runs which can be undone
+        #[arg(short, long, default_value = "false")]
+        list: bool,
+
+        /// Restore the paths as well when they are changed after the run
+        #[arg(short, long, default_value = "false")]
+        force: bool,
+    },
 }
 
 pub(crate) fn filter_checks(
@@ -99,7 +275,7 @@ pub(crate) fn filter_checks(
 }
 
 pub fn cli() -> ExitCode {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
     env_logger::Builder::new()
         .filter_level(cli.verbose.log_level_filter())
         .format(|buf, record| writeln!(buf, "{}", record.args()))
@@ -112,8 +288,13 @@ pub fn cli() -> ExitCode {
     } else {
         HashMap::new()
     };
+    variables.extend(cli.vars.iter().cloned());
+    variables::configure_overrides(std::mem::take(&mut cli.vars).into_iter().collect());
+    variables::configure_commands(
+        !(cli.list_checkers || cli.list_profiles || matches!(cli.command, Some(Command::Validate))),
+    );
 
-    let path_str = if let Some(path) = cli.path { path } else { "check_config.toml".to_string()};
+    let path_str = if let Some(path) = cli.path.take() { path } else { "check_config.toml".to_string()};
     let path = match ReadablePath::from_string(path_str.as_str(), None) {
         Ok(path) => path,
             Err(_) => {
@@ -123,24 +304,139 @@ pub fn cli() -> ExitCode {
                 return ExitCode::from(ExitStatus::Error);
             } 
     };
+    variables.extend(variables::builtin_variables(&path));
+
+    http::configure_cache(http::CacheConfig {
+        dir: http::cache_dir(),
+        ttl: std::time::Duration::from_secs(cli.cache_ttl),
+        offline: cli.offline,
+    });
+    for header in std::mem::take(&mut cli.http_headers) {
+        http::add_extra_header(header);
+    }
+    python::configure_interpreter(cli.python.take());
+    if let Err(e) = http::configure_client(&http::ClientConfig {
+        connect_timeout: std::time::Duration::from_secs(cli.connect_timeout),
+        read_timeout: std::time::Duration::from_secs(cli.read_timeout),
+        retries: cli.retries,
+        proxy: cli.proxy.take(),
+        no_proxy: cli.no_proxy.take(),
+        ca_bundle: cli.ca_bundle.take(),
+    }) {
+        log::error!("🚨 Unable to create the http client for remote files: {e}");
+        return ExitCode::from(ExitStatus::Error);
+    }
+
+    match cli.command.take() {
+        Some(Command::Validate) => return ExitCode::from(validate(&path, &mut variables)),
+        Some(Command::Undo { run, list, force }) => {
+            return ExitCode::from(undo(run.as_deref(), list, force));
+        }
+        None => (),
+    }
+
+    // with --each, the fixes of all projects are recorded in the same journal
+    let mut journal = match cli.fix && !cli.list_checkers && !cli.list_profiles {
+        true => create_journal(&path),
+        false => None,
+    };
+    let (run_report, exit_status) = match &cli.each {
+        Some(pattern) => match run_each(&cli, pattern, &path, &variables, &mut journal) {
+            Ok(result) => result,
+            Err(exit_status) => return ExitCode::from(exit_status),
+        },
+        None => match run(&cli, &path, &mut variables, &mut journal) {
+            Ok(run_report) => {
+                let exit_status = run_report.exit_status();
+                (run_report, exit_status)
+            }
+            Err(exit_status) => return ExitCode::from(exit_status),
+        },
+    };
 
-    let mut checks = read_checks_from_path(&path,  &mut variables);
+    if let Some(mut journal) = journal {
+        match journal.save() {
+            Ok(()) if !journal.paths().is_empty() => {
+                log::warn!("↩️ Undo the fixes with `check-config undo {}`", journal.id)
+            }
+            Ok(()) => (),
+            Err(e) => log::error!("🚨 Unable to save the journal for the backups: {e}"),
+        }
+    }
+
+    match cli.output_format {
+        OutputFormat::Text => run_report.log_summary(),
+        OutputFormat::Json => println!("{}", report::json::to_string(&run_report)),
+        OutputFormat::Junit => print!("{}", report::junit::to_string(&run_report)),
+        OutputFormat::Sarif => println!("{}", report::sarif::to_string(&run_report)),
+    }
+
+    ExitCode::from(exit_status)
+}
+
+fn create_journal(path: &ReadablePath) -> Option<Journal> {
+    let journal = match journal::state_dir() {
+        Some(state_dir) => Journal::create(&state_dir, &path.short_url_str()),
+        None => Err(JournalError::NoStateDir),
+    };
+    match journal {
+        Ok(journal) => Some(journal),
+        Err(e) => {
+            log::warn!(
+                "⚠ Unable to create the journal for the backups ({e}), so the fixes can not be undone"
+            );
+            None
+        }
+    }
+}
+
+/// The error is the exit status when no checkers are executed, like when they are listed.
+fn run(
+    cli: &Cli,
+    path: &ReadablePath,
+    variables: &mut HashMap<String, String>,
+    journal: &mut Option<Journal>,
+) -> Result<RunReport, ExitStatus> {
+    let check_definitions = read_checks_from_path(path, variables);
+    let definition_issues = check_definitions.issues.len();
+    let mut checks = check_definitions.checks;
 
     log::info!("Fix: {}", &cli.fix);
 
+    if cli.list_profiles {
+        list_profiles(&check_definitions.profiles);
+        return Err(ExitStatus::Success);
+    }
+
+    let mut tag_selection = match &cli.profile {
+        Some(profile) => match resolve_profile(&check_definitions.profiles, profile) {
+            Ok(tag_selection) => tag_selection,
+            Err(e) => {
+                log::error!("🚨 Unable to select the checkers: {e}");
+                return Err(ExitStatus::Error);
+            }
+        },
+        None => TagSelection::default(),
+    };
+    tag_selection.extend(&TagSelection {
+        any_tags: cli.any_tags.clone(),
+        all_tags: cli.all_tags.clone(),
+        skip_tags: cli.skip_tags.clone(),
+    });
+
     if cli.list_checkers {
         log::error!("List of checks (type, location of definition, file to check, tags)");
         checks.iter().for_each(|check| {
             let enabled = filter_checks(
                 &check.generic_checker().tags,
-                &cli.any_tags,
-                &cli.all_tags,
-                &cli.skip_tags,
+                &tag_selection.any_tags,
+                &tag_selection.all_tags,
+                &tag_selection.skip_tags,
             );
 
             check.list_checker(enabled);
         });
-        return ExitCode::from(ExitStatus::Success);
+        return Err(ExitStatus::Success);
     }
 
     // log::info!(
@@ -151,55 +447,651 @@ pub fn cli() -> ExitCode {
     checks.retain(|check| {
         filter_checks(
             &check.generic_checker().tags,
-            &cli.any_tags,
-            &cli.all_tags,
-            &cli.skip_tags,
+            &tag_selection.any_tags,
+            &tag_selection.all_tags,
+            &tag_selection.skip_tags,
         )
     });
 
-    ExitCode::from(run_checks(&checks, cli.fix))
+    if let Some(patch_path) = &cli.emit_patch {
+        let patch = collect_patch(&checks);
+        if let Err(e) = std::fs::write(patch_path, patch.to_diff()) {
+            log::error!(
+                "🚨 Unable to write patch to {}: {e}",
+                patch_path.to_string_lossy()
+            );
+            return Err(ExitStatus::Error);
+        }
+        log::warn!(
+            "🩹 Patch with changes to {} files written to {}",
+            patch.patched_files().len(),
+            patch_path.to_string_lossy()
+        );
+    }
+
+    let mut run_report = if cli.interactive {
+        run_checks_interactively(&checks, journal, &mut ask_fix_confirmation)
+    } else {
+        run_checks(&checks, cli.fix, journal)
+    };
+    run_report.definition_issues = definition_issues;
+    Ok(run_report)
+}
+
+pub(crate) fn project_dirs(pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let pattern =
+        WritablePath::from_string(pattern).map_err(|_| format!("{pattern} is not a valid path"))?;
+    let mut dirs = glob::expand(pattern.as_ref())?;
+    dirs.retain(|dir| dir.is_dir());
+    Ok(dirs)
+}
+
+fn project_name(dir: &Path) -> String {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    dir.strip_prefix(&cwd)
+        .unwrap_or(dir)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn run_each(
+    cli: &Cli,
+    pattern: &str,
+    path: &ReadablePath,
+    variables: &HashMap<String, String>,
+    journal: &mut Option<Journal>,
+) -> Result<(RunReport, ExitStatus), ExitStatus> {
+    let dirs = match project_dirs(pattern) {
+        Ok(dirs) if dirs.is_empty() => {
+            log::error!("🚨 No project dirs match {pattern}");
+            return Err(ExitStatus::Error);
+        }
+        Ok(dirs) => dirs,
+        Err(e) => {
+            log::error!("🚨 Unable to find the project dirs: {e}");
+            return Err(ExitStatus::Error);
+        }
+    };
+
+    let mut run_report = RunReport::new(cli.fix);
+    let mut project_results = vec![];
+    for dir in dirs {
+        let project = project_name(&dir);
+        log::warn!("📁 {project}");
+
+        let result = uri::with_base_dir(&dir, || {
+            let mut variables = variables.clone();
+            variables.extend(variables::builtin_variables(path));
+            run(cli, path, &mut variables, journal)
+        });
+        match result {
+            Ok(project_report) => {
+                project_results.push((project.clone(), Ok(project_report.clone())));
+                run_report.extend_with_project(&project, project_report);
+            }
+            // the checkers or profiles are listed
+            Err(ExitStatus::Success) => (),
+            Err(exit_status) => project_results.push((project, Err(exit_status))),
+        }
+    }
+
+    if cli.list_checkers || cli.list_profiles {
+        return Err(ExitStatus::Success);
+    }
+
+    let mut exit_status = run_report.exit_status();
+    if cli.output_format == OutputFormat::Text {
+        log::warn!("📁 {} projects", project_results.len());
+    }
+    for (project, result) in project_results {
+        match result {
+            Ok(project_report) if cli.output_format == OutputFormat::Text => {
+                project_report.log_project_summary(&project)
+            }
+            Ok(_) => (),
+            Err(_) => {
+                log::error!("🚨 {project}: the checkers are not executed");
+                exit_status = ExitStatus::Error;
+            }
+        }
+    }
+
+    Ok((run_report, exit_status))
 }
 
-pub(crate) fn run_checks(checks: &Vec<Box<dyn Checker>>, fix: bool) -> ExitStatus {
-    let mut fix_needed_count = 0;
-    let mut fix_executed_count = 0;
-    let mut no_fix_needed_count = 0;
-    let mut error_count = 0;
+fn list_profiles(profiles: &BTreeMap<String, Profile>) {
+    log::error!("List of profiles (name, inherited profile, any tags, all tags, skip tags)");
+    for (name, profile) in profiles {
+        match resolve_profile(profiles, name) {
+            Ok(tag_selection) => log::error!(
+                "⬜ {name} - {} - {:?} - {:?} - {:?}",
+                profile.inherits.as_deref().unwrap_or("-"),
+                tag_selection.any_tags,
+                tag_selection.all_tags,
+                tag_selection.skip_tags
+            ),
+            Err(e) => log::error!("🚨 {name} - {e}"),
+        }
+    }
+}
+
+pub(crate) fn validate(path: &ReadablePath, variables: &mut HashMap<String, String>) -> ExitStatus {
+    let check_definitions = load_checks_from_path(path, variables);
+
+    for issue in check_definitions.issues.iter() {
+        log::error!("🚨 {issue}");
+    }
+    let profile_errors = check_definitions
+        .profiles
+        .keys()
+        .filter_map(|name| resolve_profile(&check_definitions.profiles, name).err())
+        .collect::<Vec<_>>();
+    for profile_error in profile_errors.iter() {
+        log::error!("🚨 {}: {profile_error}", path.short_url_str());
+    }
+
+    log::warn!(
+        "⬜ {checks} checks found",
+        checks = check_definitions.checks.len()
+    );
+    match check_definitions.issues.len() + profile_errors.len() {
+        0 => {
+            log::error!("🥇 No problems found in the checkers.");
+            ExitStatus::Success
+        }
+        1 => {
+            log::error!("🚨 There is 1 problem in the checkers.");
+            ExitStatus::Error
+        }
+        issue_count => {
+            log::error!("🚨 There are {issue_count} problems in the checkers.");
+            ExitStatus::Error
+        }
+    }
+}
+
+pub(crate) fn undo(run: Option<&str>, list: bool, force: bool) -> ExitStatus {
+    let Some(state_dir) = journal::state_dir() else {
+        log::error!("🚨 Unable to undo: {}", JournalError::NoStateDir);
+        return ExitStatus::Error;
+    };
+
+    if list {
+        return match Journal::list(&state_dir) {
+            Ok(journals) => {
+                for journal in journals {
+                    log::error!(
+                        "{} {} - {} - {} paths",
+                        if journal.undone { " ✖️" } else { "⬜" },
+                        journal.id,
+                        journal.file_with_checks,
+                        journal.paths().len()
+                    );
+                }
+                ExitStatus::Success
+            }
+            Err(e) => {
+                log::error!("🚨 Unable to read the journals: {e}");
+                ExitStatus::Error
+            }
+        };
+    }
+
+    let result = Journal::find(&state_dir, run).and_then(|mut journal| {
+        journal.undo(force)?;
+        Ok(journal)
+    });
+    match result {
+        Ok(journal) => {
+            for path in journal.paths() {
+                log::info!("↩️ {} restored", path.to_string_lossy());
+            }
+            log::error!(
+                "🥇 Run {} undone, {} paths restored.",
+                journal.id,
+                journal.paths().len()
+            );
+            ExitStatus::Success
+        }
+        Err(e) => {
+            log::error!("🚨 Unable to undo: {e}");
+            ExitStatus::Error
+        }
+    }
+}
+
+pub(crate) fn run_checks(
+    checks: &Vec<Box<dyn Checker>>,
+    fix: bool,
+    journal: &mut Option<Journal>,
+) -> RunReport {
+    let mut run_report = RunReport::new(fix);
+    let mut verifier = FixVerifier::new();
+    let mut failed_dependencies = FailedDependencies::new();
 
     for check in checks {
         let fix = !check.generic_checker().check_only && fix;
-        let result = check.check(fix);
+        let result = if let Some(result) = failed_dependencies.skipped_result(check.as_ref()) {
+            check.print(&result);
+            result
+        } else if fix {
+            fix_check(check.as_ref(), journal, &mut verifier)
+        } else {
+            check.check(false)
+        };
+        failed_dependencies.record(check.as_ref(), &result);
+        run_report.push(check.report(result));
+    }
+
+    run_report
+}
+
+fn fix_check(
+    check: &dyn Checker,
+    journal: &mut Option<Journal>,
+    verifier: &mut FixVerifier,
+) -> CheckResult {
+    if let Some(journal) = journal {
+        for path in check.touched_paths() {
+            if let Err(e) = journal.backup(&path) {
+                journal.discard();
+                let result = CheckResult::Error(format!(
+                    "backup of {} failed ({e})",
+                    path.to_string_lossy()
+                ));
+                check.print(&result);
+                return result;
+            }
+        }
+    }
+
+    verifier.before_fix(check);
+    let result = check.check(true);
+
+    if let Some(journal) = journal {
         match result {
-            crate::checkers::base::CheckResult::NoFixNeeded => no_fix_needed_count += 1,
-            crate::checkers::base::CheckResult::FixExecuted(_) => fix_executed_count += 1,
-            crate::checkers::base::CheckResult::FixNeeded(_) => fix_needed_count += 1,
-            crate::checkers::base::CheckResult::Error(_) => error_count += 1,
+            // a failing fix might have changed some of the paths
+            CheckResult::FixExecuted(_) | CheckResult::Error(_) => {
+                if let Err(e) = journal.commit() {
+                    log::error!("🚨 Unable to save the journal for the backups: {e}");
+                }
+            }
+            _ => journal.discard(),
+        }
+    }
+
+    let verified_result = verifier.verify(check, result.clone());
+    if verified_result != result {
+        check.print(&verified_result);
+    }
+    verified_result
+}
+
+pub(crate) fn collect_patch(checks: &Vec<Box<dyn Checker>>) -> Patch {
+    let mut patch = Patch::new();
+
+    for check in checks {
+        if check.generic_checker().check_only || check.skipped_by_condition().is_some() {
+            continue;
+        }
+        if let Err(e) = check.patch(&mut patch) {
+            log::error!(
+                "🚨 {} - {} - {} - fix is not added to the patch: {e}",
+                check.generic_checker().file_with_checks.short_url_str(),
+                check.checker_type(),
+                check.checker_object(),
+            );
+        }
+    }
+
+    patch
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum FixConfirmation {
+    Apply,
+    Skip,
+    ApplyAll,
+    Quit,
+}
+
+fn ask_fix_confirmation(check: &dyn Checker, action_message: &str) -> FixConfirmation {
+    eprintln!(
+        "❌ {} - {} - {}\n{}",
+        check.generic_checker().file_with_checks.short_url_str(),
+        check.checker_type(),
+        check.checker_object(),
+        action_message.trim_end()
+    );
+
+    let stdin = std::io::stdin();
+    loop {
+        eprint!("Apply fix? [y]es, [n]o, [a]ll, [q]uit: ");
+        let _ = std::io::stderr().flush();
+
+        let mut answer = String::new();
+        match stdin.lock().read_line(&mut answer) {
+            // no more input, so no more fixes can be confirmed
+            Ok(0) | Err(_) => return FixConfirmation::Quit,
+            Ok(_) => (),
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => return FixConfirmation::Apply,
+            "n" | "no" => return FixConfirmation::Skip,
+            "a" | "all" => return FixConfirmation::ApplyAll,
+            "q" | "quit" => return FixConfirmation::Quit,
+            _ => (),
+        }
+    }
+}
+
+pub(crate) fn run_checks_interactively(
+    checks: &Vec<Box<dyn Checker>>,
+    journal: &mut Option<Journal>,
+    confirm: &mut dyn FnMut(&dyn Checker, &str) -> FixConfirmation,
+) -> RunReport {
+    let mut run_report = RunReport::new(true);
+    let mut apply_all = false;
+    let mut quit = false;
+    let mut verifier = FixVerifier::new();
+    let mut failed_dependencies = FailedDependencies::new();
+
+    for check in checks {
+        let fix_allowed = !check.generic_checker().check_only && !quit;
+
+        if let Some(result) = failed_dependencies.skipped_result(check.as_ref()) {
+            check.print(&result);
+            failed_dependencies.record(check.as_ref(), &result);
+            run_report.push(check.report(result));
+            continue;
+        }
+
+        if fix_allowed && apply_all {
+            let result = fix_check(check.as_ref(), journal, &mut verifier);
+            failed_dependencies.record(check.as_ref(), &result);
+            run_report.push(check.report(result));
+            continue;
+        }
+
+        let mut result = check.check(false);
+
+        if fix_allowed && let CheckResult::FixNeeded(action_message) = &result {
+            match confirm(check.as_ref(), action_message) {
+                FixConfirmation::Apply => {
+                    result = fix_check(check.as_ref(), journal, &mut verifier)
+                }
+                FixConfirmation::ApplyAll => {
+                    apply_all = true;
+                    result = fix_check(check.as_ref(), journal, &mut verifier);
+                }
+                FixConfirmation::Skip => (),
+                FixConfirmation::Quit => quit = true,
+            }
+        }
+
+        failed_dependencies.record(check.as_ref(), &result);
+        run_report.push(check.report(result));
+    }
+
+    run_report
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checkers::{
+        GenericChecker,
+        base::CheckConstructor,
+        file::{file_present::FilePresent, lines_present::LinesPresent},
+        test_helpers,
+    };
+
+    use super::*;
+
+    fn get_file_present_checks(dir: &std::path::Path, count: usize) -> Vec<Box<dyn Checker>> {
+        (0..count)
+            .map(|i| {
+                let mut check_table = toml_edit::Table::new();
+                check_table.insert(
+                    "file",
+                    dir.join(format!("file_{i}"))
+                        .to_string_lossy()
+                        .to_string()
+                        .into(),
+                );
+                let generic_check = GenericChecker {
+                    check_only: false,
+                    ..test_helpers::get_generic_check()
+                };
+                Box::new(FilePresent::from_check_table(generic_check, check_table).unwrap())
+                    as Box<dyn Checker>
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_run_checks_interactively() {
+        let dir = tempfile::tempdir().unwrap();
+        let checks = get_file_present_checks(dir.path(), 4);
+
+        let mut answers = vec![
+            FixConfirmation::Skip,
+            FixConfirmation::Apply,
+            FixConfirmation::Quit,
+        ];
+        answers.reverse();
+        let mut confirm = |_: &dyn Checker, action_message: &str| {
+            assert_eq!(action_message, "create file");
+            answers.pop().expect("no more questions expected")
         };
+
+        let run_report = run_checks_interactively(&checks, &mut None, &mut confirm);
+
+        assert_eq!(
+            run_report
+                .checks
+                .iter()
+                .map(|check| check.result.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                CheckResult::FixNeeded("create file".into()),
+                CheckResult::FixExecuted("create file".into()),
+                CheckResult::FixNeeded("create file".into()),
+                CheckResult::FixNeeded("create file".into()),
+            ]
+        );
+        assert!(!dir.path().join("file_0").exists());
+        assert!(dir.path().join("file_1").exists());
+        assert!(!dir.path().join("file_3").exists());
+    }
+
+    #[test]
+    fn test_collect_patch() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        std::fs::write(&file, "a\n").unwrap();
+
+        let mut checks = get_file_present_checks(dir.path(), 1);
+        for (lines, check_only) in [("b\n", false), ("c\n", true), ("d\n", false)] {
+            let mut check_table = toml_edit::Table::new();
+            check_table.insert("file", file.to_string_lossy().to_string().into());
+            check_table.insert("lines", lines.into());
+            let generic_check = GenericChecker {
+                check_only,
+                ..test_helpers::get_generic_check()
+            };
+            checks.push(Box::new(
+                LinesPresent::from_check_table(generic_check, check_table).unwrap(),
+            ));
+        }
+
+        let patch = collect_patch(&checks);
+
+        assert_eq!(
+            patch
+                .changed_files()
+                .iter()
+                .map(|change| (change.path.clone(), change.new_contents.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (dir.path().join("file_0"), Some("".into())),
+                (file.clone(), Some("a\n\nb\n\nd\n".into())),
+            ]
+        );
+        // the fixes are not executed
+        assert!(!dir.path().join("file_0").exists());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "a\n");
+    }
+
+    #[test]
+    fn test_run_checks_with_journal() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("file_0"), "existing").unwrap();
+        let checks = get_file_present_checks(dir.path(), 2);
+
+        let mut journal = Some(Journal::create(state_dir.path(), "check-config.toml").unwrap());
+        let run_report = run_checks(&checks, true, &mut journal);
+        journal.unwrap().save().unwrap();
+
+        assert_eq!(run_report.fix_executed_count(), 1);
+        assert!(dir.path().join("file_1").exists());
+
+        let mut journal = Journal::find(state_dir.path(), None).unwrap();
+        // only the paths changed by a fix are recorded
+        assert_eq!(journal.paths(), vec![dir.path().join("file_1").as_path()]);
+        journal.undo(false).unwrap();
+
+        assert!(!dir.path().join("file_1").exists());
+        assert!(dir.path().join("file_0").exists());
     }
 
-    log::warn!("⬜ {checks} checks found", checks = checks.len());
-    if fix {
-        log::warn!("✅ {fix_executed_count} checks fixed");
-        log::warn!("✅ {no_fix_needed_count} checks did not need a fix");
+    #[test]
+    fn test_project_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        for project in ["services/a", "services/b", "services/.hidden"] {
+            std::fs::create_dir_all(dir.path().join(project)).unwrap();
+        }
+        std::fs::write(dir.path().join("services/README.md"), "").unwrap();
+
+        let project_dirs = uri::with_base_dir(dir.path(), || project_dirs("services/*")).unwrap();
+
+        assert_eq!(
+            project_dirs,
+            vec![dir.path().join("services/a"), dir.path().join("services/b")]
+        );
+    }
+
+    #[test]
+    fn test_run_each() {
+        let dir = tempfile::tempdir().unwrap();
+        for project in ["services/a", "services/b"] {
+            std::fs::create_dir_all(dir.path().join(project)).unwrap();
+        }
+        std::fs::write(dir.path().join("services/a/README.md"), "").unwrap();
+        let file_with_checks = dir.path().join("check-config.toml");
+        std::fs::write(
+            &file_with_checks,
+            "[[file_present]]\nfile = \"README.md\"\n",
+        )
+        .unwrap();
+        let path = ReadablePath::from_string(file_with_checks.to_str().unwrap(), None).unwrap();
+        let cli = Cli::parse_from(["check-config", "--each", "services/*"]);
+
+        let (run_report, exit_status) = uri::with_base_dir(dir.path(), || {
+            run_each(&cli, "services/*", &path, &HashMap::new(), &mut None)
+        })
+        .unwrap();
+
+        assert_eq!(
+            run_report
+                .checks
+                .iter()
+                .map(|check| (check.project.clone(), check.result.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Some(project_name(&dir.path().join("services/a"))),
+                    CheckResult::NoFixNeeded
+                ),
+                (
+                    Some(project_name(&dir.path().join("services/b"))),
+                    CheckResult::FixNeeded("create file".into())
+                ),
+            ]
+        );
+        assert_eq!(exit_status, ExitStatus::Failure);
     }
 
-    match fix_needed_count {
-        0 => log::error!("🥇 No violations found."),
-        1 => log::error!("🪛 There is 1 violation to fix.",),
-        _ => log::error!("🪛 There are {fix_needed_count} violations to fix.",),
+    #[test]
+    fn test_run_each_adds_http_headers_once() {
+        let dir = tempfile::tempdir().unwrap();
+        for project in ["a", "b"] {
+            std::fs::create_dir_all(dir.path().join(project)).unwrap();
+        }
+        let file_with_checks = dir.path().join("check-config.toml");
+        std::fs::write(
+            &file_with_checks,
+            r#"
+[http_headers."each.example.com"]
+Authorization = "Bearer secret"
+X-Api-Version = "2"
+
+[[file_present]]
+file = "README.md"
+"#,
+        )
+        .unwrap();
+        let path = ReadablePath::from_string(file_with_checks.to_str().unwrap(), None).unwrap();
+        let cli = Cli::parse_from(["check-config", "--each", "*"]);
+
+        uri::with_base_dir(dir.path(), || {
+            run_each(&cli, "*", &path, &HashMap::new(), &mut None)
+        })
+        .unwrap();
+
+        let extra_headers = http::EXTRA_HEADERS.lock().unwrap();
+        assert_eq!(
+            extra_headers
+                .iter()
+                .filter(|header| header.host.as_deref() == Some("each.example.com"))
+                .map(|header| header.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["authorization", "x-api-version"]
+        );
     }
 
-    match error_count {
-        0 => (),
+    #[test]
+    fn test_run_with_invalid_checker() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_with_checks = dir.path().join("check-config.toml");
+        std::fs::write(
+            &file_with_checks,
+            "[[file_present]]\nfile = \"README.md\"\nregex = \"[0-9\"\n",
+        )
+        .unwrap();
+        let path = ReadablePath::from_string(file_with_checks.to_str().unwrap(), None).unwrap();
+        let cli = Cli::parse_from(["check-config"]);
 
-        1 => log::error!("🚨 There was 1 error executing a fix.",),
-        _ => log::error!("🚨 There are {error_count} errors executing a fix.",),
+        let run_report = uri::with_base_dir(dir.path(), || {
+            run(&cli, &path, &mut HashMap::new(), &mut None)
+        })
+        .unwrap();
+
+        // the invalid checker is not executed, but it is not silently ignored
+        assert!(run_report.checks.is_empty());
+        assert_eq!(run_report.definition_issues, 1);
+        assert_eq!(run_report.exit_status(), ExitStatus::Error);
     }
-    if error_count > 0 {
-        ExitStatus::Error
-    } else if fix_needed_count > 0 {
-        ExitStatus::Failure
-    } else {
-        ExitStatus::Success
+
+    #[test]
+    fn test_run_checks_interactively_apply_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let checks = get_file_present_checks(dir.path(), 3);
+
+        let mut confirm = |_: &dyn Checker, _: &str| FixConfirmation::ApplyAll;
+
+        let run_report = run_checks_interactively(&checks, &mut None, &mut confirm);
+
+        assert_eq!(run_report.fix_executed_count(), 3);
     }
 }
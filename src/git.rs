@@ -0,0 +1,411 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use git2::{Cred, CredentialType, ErrorCode, FetchOptions, Oid, RemoteCallbacks, Repository};
+use sha2::{Digest, Sha256};
+use url::{Position, Url};
+
+use crate::{
+    http::{self, CacheConfig, Credentials},
+    uri::PathError,
+};
+
+// the repos fetched during this run, so a repo is fetched once per run
+static FETCHED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+/// A file in a git repo at a ref, like `git+https://host/org/repo.git@v1.2#path/to/file.toml`
+#[derive(Debug, PartialEq)]
+pub(crate) struct GitUrl {
+    pub(crate) repo: String,
+    /// The default branch is used when it is not set.
+    pub(crate) reference: Option<String>,
+    pub(crate) path: String,
+}
+
+impl GitUrl {
+    pub(crate) fn parse(url: &Url) -> Result<Self, PathError> {
+        let invalid = || PathError::InvalidGitUrl(url.to_string());
+        let base = url[..Position::BeforePath]
+            .strip_prefix("git+")
+            .ok_or_else(invalid)?;
+        let (repo_path, reference) = match url.path().split_once('@') {
+            Some((repo_path, reference)) if !reference.is_empty() => {
+                (repo_path, Some(reference.to_string()))
+            }
+            Some(_) => return Err(invalid()),
+            None => (url.path(), None),
+        };
+        let path = url
+            .fragment()
+            .map(|path| path.trim_start_matches('/'))
+            .filter(|path| !path.is_empty())
+            .ok_or_else(invalid)?;
+
+        Ok(Self {
+            repo: format!("{base}{repo_path}"),
+            reference,
+            path: path.to_string(),
+        })
+    }
+}
+
+pub(crate) fn join(url: &Url, relative: &str) -> Result<Url, url::ParseError> {
+    // the path in the repo is resolved as the path of a file url
+    let joined = Url::parse("file:///")?
+        .join(url.fragment().unwrap_or_default())?
+        .join(relative)?;
+    let mut url = url.clone();
+    url.set_fragment(Some(joined.path().trim_start_matches('/')));
+    Ok(url)
+}
+
+pub(crate) fn read(url: &Url) -> Result<Option<Vec<u8>>, PathError> {
+    read_with_cache(url, &http::cache_config())
+}
+
+fn read_with_cache(url: &Url, config: &CacheConfig) -> Result<Option<Vec<u8>>, PathError> {
+    let git_url = GitUrl::parse(url)?;
+    let mut repo = fetched_repo(&git_url, config, false)?;
+    // a new tag or branch is not in a clone which is fetched within the ttl
+    if resolve(&repo, git_url.reference.as_deref()).is_err() {
+        repo = fetched_repo(&git_url, config, true)?;
+    }
+    let tree = resolve(&repo, git_url.reference.as_deref())?.tree()?;
+
+    let entry = match tree.get_path(Path::new(&git_url.path)) {
+        Ok(entry) => entry,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(entry
+        .to_object(&repo)?
+        .into_blob()
+        .ok()
+        .map(|blob| blob.content().to_vec()))
+}
+
+fn repo_dir(config: &CacheConfig, repo: &str) -> PathBuf {
+    config
+        .dir
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("check-config"))
+        .join("git")
+        .join(format!("{:x}", Sha256::digest(repo)))
+}
+
+/// The repo is fetched when the ref may have changed since the last fetch, or when `refetch` is
+/// set. A repo is fetched at most once per run.
+fn fetched_repo(
+    git_url: &GitUrl,
+    config: &CacheConfig,
+    refetch: bool,
+) -> Result<Repository, PathError> {
+    let dir = repo_dir(config, &git_url.repo);
+    let repo = match Repository::open_bare(&dir) {
+        Ok(repo) => repo,
+        Err(_) => Repository::init_bare(&dir)?,
+    };
+
+    let mut fetched = FETCHED
+        .get_or_init(Default::default)
+        .lock()
+        .expect("lock is not poisoned");
+    if config.offline
+        || fetched.contains(&git_url.repo)
+        || (!refetch
+            && (is_fresh(&repo, config.ttl)
+                || is_cached_commit(&repo, git_url.reference.as_deref())))
+    {
+        return Ok(repo);
+    }
+
+    match fetch(&repo, &git_url.repo) {
+        Ok(()) => {
+            fetched.insert(git_url.repo.clone());
+        }
+        Err(e) if repo.references()?.next().is_some() => {
+            log::warn!(
+                "⚠ {} can not be fetched ({e}), the cached repo is used",
+                git_url.repo
+            );
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(repo)
+}
+
+// the number of times the credentials are asked for one fetch, after which the credentials
+// are considered to be rejected
+const MAX_CREDENTIAL_ATTEMPTS: usize = 4;
+
+fn credentials(
+    url: &str,
+    username_from_url: Option<&str>,
+    allowed_types: CredentialType,
+    attempt: usize,
+) -> Result<Cred, git2::Error> {
+    if attempt > MAX_CREDENTIAL_ATTEMPTS {
+        return Err(git2::Error::from_str("the credentials are rejected"));
+    }
+    if allowed_types.contains(CredentialType::SSH_KEY) {
+        return Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+    }
+    if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) {
+        let parsed = Url::parse(url).ok();
+        // like the requests of remote files, credentials are only sent over https
+        if let Some(url) = parsed.as_ref().filter(|url| url.scheme() == "https") {
+            // the host credentials are tried first, the credential helper on the next attempt
+            let host_credentials = url
+                .host_str()
+                .and_then(http::host_credentials)
+                .filter(|_| attempt == 1);
+            match host_credentials {
+                Some(Credentials::Bearer(token)) => {
+                    return Cred::userpass_plaintext(
+                        username_from_url.unwrap_or("x-access-token"),
+                        &token,
+                    );
+                }
+                Some(Credentials::Basic { user, password }) => {
+                    return Cred::userpass_plaintext(&user, &password);
+                }
+                None => {
+                    let config = git2::Config::open_default()?;
+                    return Cred::credential_helper(&config, url.as_str(), username_from_url);
+                }
+            }
+        }
+    }
+    if allowed_types.contains(CredentialType::DEFAULT) {
+        return Cred::default();
+    }
+    Err(git2::Error::from_str("no credentials are available"))
+}
+
+fn fetch(repo: &Repository, url: &str) -> Result<(), git2::Error> {
+    let mut attempt = 0;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|url, username_from_url, allowed_types| {
+        attempt += 1;
+        credentials(url, username_from_url, allowed_types, attempt)
+    });
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+
+    repo.remote_anonymous(url)?.fetch(
+        &[
+            "+refs/heads/*:refs/remotes/origin/*",
+            "+refs/tags/*:refs/tags/*",
+            "+HEAD:refs/remotes/origin/HEAD",
+        ],
+        Some(&mut fetch_options),
+        None,
+    )
+}
+
+fn is_fresh(repo: &Repository, ttl: Duration) -> bool {
+    std::fs::metadata(repo.path().join("FETCH_HEAD"))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed < ttl)
+}
+
+fn is_cached_commit(repo: &Repository, reference: Option<&str>) -> bool {
+    reference
+        .filter(|reference| reference.len() == 40)
+        .and_then(|reference| Oid::from_str(reference).ok())
+        .is_some_and(|oid| repo.find_commit(oid).is_ok())
+}
+
+fn resolve<'r>(
+    repo: &'r Repository,
+    reference: Option<&str>,
+) -> Result<git2::Commit<'r>, git2::Error> {
+    let Some(reference) = reference else {
+        return repo
+            .find_reference("refs/remotes/origin/HEAD")?
+            .peel_to_commit();
+    };
+    for name in [
+        format!("refs/tags/{reference}"),
+        format!("refs/remotes/origin/{reference}"),
+    ] {
+        if let Ok(reference) = repo.find_reference(&name) {
+            return reference.peel_to_commit();
+        }
+    }
+    repo.revparse_single(reference)?.peel_to_commit()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use git2::Signature;
+
+    use super::*;
+
+    pub(crate) fn create_repo(dir: &Path, files: &[(&str, &str)], tag: &str) -> Oid {
+        let repo = Repository::open(dir)
+            .or_else(|_| Repository::init(dir))
+            .unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, contents) in files {
+            let file = dir.join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(&file, contents).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        let commit = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                tag,
+                &tree,
+                &parent.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        repo.tag_lightweight(tag, &repo.find_object(commit, None).unwrap(), false)
+            .unwrap();
+        commit
+    }
+
+    #[test]
+    fn test_parse_git_url() {
+        assert_eq!(
+            GitUrl::parse(
+                &Url::parse("git+https://user@example.com/org/repo.git@release/v1#/dir/file.toml")
+                    .unwrap()
+            )
+            .unwrap(),
+            GitUrl {
+                repo: "https://user@example.com/org/repo.git".into(),
+                reference: Some("release/v1".into()),
+                path: "dir/file.toml".into(),
+            }
+        );
+        assert_eq!(
+            GitUrl::parse(&Url::parse("git+file:///repo#file.toml").unwrap())
+                .unwrap()
+                .reference,
+            None
+        );
+        for url in [
+            "git+https://example.com/repo.git@v1",
+            "git+https://example.com/repo.git@#file.toml",
+            "https://example.com/repo.git#file.toml",
+        ] {
+            assert!(matches!(
+                GitUrl::parse(&Url::parse(url).unwrap()),
+                Err(PathError::InvalidGitUrl(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_join() {
+        let url = Url::parse("git+https://example.com/repo.git@v1#dir/file.toml").unwrap();
+        assert_eq!(
+            join(&url, "other.toml").unwrap().as_str(),
+            "git+https://example.com/repo.git@v1#dir/other.toml"
+        );
+        assert_eq!(
+            join(&url, "../other.toml").unwrap().as_str(),
+            "git+https://example.com/repo.git@v1#other.toml"
+        );
+    }
+
+    #[test]
+    fn test_read_with_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_dir = dir.path().join("repo");
+        let v1 = create_repo(&repo_dir, &[("dir/file.toml", "v1")], "v1");
+        create_repo(&repo_dir, &[("dir/file.toml", "v2")], "v2");
+        let mut config = CacheConfig {
+            dir: Some(dir.path().join("cache")),
+            ..Default::default()
+        };
+        let read = |reference: &str, path: &str, config: &CacheConfig| {
+            let url = Url::parse(&format!(
+                "git+file://{}{reference}#{path}",
+                repo_dir.to_string_lossy()
+            ))
+            .unwrap();
+            read_with_cache(&url, config)
+        };
+
+        assert_eq!(
+            read("@v1", "dir/file.toml", &config).unwrap(),
+            Some(b"v1".to_vec())
+        );
+        assert_eq!(
+            read("", "dir/file.toml", &config).unwrap(),
+            Some(b"v2".to_vec())
+        );
+        assert_eq!(
+            read(
+                &format!("@{}", &v1.to_string()[..8]),
+                "dir/file.toml",
+                &config
+            )
+            .unwrap(),
+            Some(b"v1".to_vec())
+        );
+        assert_eq!(read("@v1", "dir/other.toml", &config).unwrap(), None);
+        assert_eq!(read("@v1", "dir", &config).unwrap(), None);
+        assert!(matches!(
+            read("@unknown", "dir/file.toml", &config),
+            Err(PathError::Git(_))
+        ));
+
+        // a new tag is fetched, although the cached clone is fetched within the ttl
+        config.ttl = Duration::from_secs(3600);
+        create_repo(&repo_dir, &[("dir/file.toml", "v3")], "v3");
+        let repo_url = format!("file://{}", repo_dir.to_string_lossy());
+        // a next run, as a repo is fetched once per run
+        FETCHED.get().unwrap().lock().unwrap().remove(&repo_url);
+        assert_eq!(
+            read("@v3", "dir/file.toml", &config).unwrap(),
+            Some(b"v3".to_vec())
+        );
+
+        // the cached clone is used when the repo is gone
+        std::fs::remove_dir_all(&repo_dir).unwrap();
+        config.offline = true;
+        assert_eq!(
+            read("@v2", "dir/file.toml", &config).unwrap(),
+            Some(b"v2".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_credentials() {
+        assert!(
+            credentials(
+                "https://example.com/repo.git",
+                None,
+                CredentialType::USER_PASS_PLAINTEXT,
+                MAX_CREDENTIAL_ATTEMPTS + 1
+            )
+            .is_err()
+        );
+        // no credentials are sent over http
+        assert!(
+            credentials(
+                "http://example.com/repo.git",
+                None,
+                CredentialType::USER_PASS_PLAINTEXT,
+                1
+            )
+            .is_err()
+        );
+    }
+}
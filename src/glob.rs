@@ -0,0 +1,172 @@
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+
+pub(crate) fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// A path with only `[...]`, like `app/[slug]/page.tsx`, may be a path with brackets in its name
+/// instead of a glob.
+pub(crate) fn has_wildcard(path: &str) -> bool {
+    path.contains(['*', '?'])
+}
+
+fn component_regex(component: &str) -> Result<Regex, String> {
+    let mut regex = String::from("^");
+    let mut chars = component.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                let mut class = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') if !class.is_empty() => break,
+                        Some('!') if class.is_empty() => class.push('^'),
+                        Some('\\') => class.push_str("\\\\"),
+                        Some(c) => class.push(c),
+                        None => return Err(format!("`[` in `{component}` is not closed")),
+                    }
+                }
+                regex.push_str(&format!("[{class}]"));
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|_| format!("`{component}` is not a valid pattern"))
+}
+
+fn entries(dir: &Path, hidden: bool) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut entries = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().to_str()?.to_string();
+            (hidden || !name.starts_with('.')).then(|| (name, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+fn dirs_below(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![dir.to_path_buf()];
+    for (_, path) in entries(dir, false) {
+        if path
+            .symlink_metadata()
+            .is_ok_and(|metadata| metadata.is_dir())
+        {
+            dirs.extend(dirs_below(&path));
+        }
+    }
+    dirs
+}
+
+/// Like a shell, a wildcard does not match a name which starts with a `.`, unless the pattern
+/// starts with a `.` as well.
+pub(crate) fn expand(pattern: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let Component::Normal(component) = component else {
+            paths.iter_mut().for_each(|path| path.push(component));
+            continue;
+        };
+        let component = component
+            .to_str()
+            .ok_or_else(|| format!("{} is not valid unicode", pattern.to_string_lossy()))?;
+
+        paths = match component {
+            "**" => paths.iter().flat_map(|path| dirs_below(path)).collect(),
+            component if is_pattern(component) => {
+                let regex = component_regex(component)?;
+                paths
+                    .iter()
+                    .flat_map(|path| entries(path, component.starts_with('.')))
+                    .filter(|(name, _)| regex.is_match(name))
+                    .map(|(_, path)| path)
+                    .collect()
+            }
+            component => paths.iter().map(|path| path.join(component)).collect(),
+        };
+    }
+
+    paths.retain(|path| path.symlink_metadata().is_ok());
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [
+            "packages/a/pyproject.toml",
+            "packages/b/pyproject.toml",
+            "packages/b/nested/pyproject.toml",
+            "packages/.hidden/pyproject.toml",
+            "packages/_c/setup.cfg",
+        ] {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let expand = |pattern: &str| {
+            expand(&dir.path().join(pattern))
+                .unwrap()
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(dir.path())
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            expand("packages/*/pyproject.toml"),
+            vec!["packages/a/pyproject.toml", "packages/b/pyproject.toml"]
+        );
+        assert_eq!(
+            expand("packages/**/pyproject.toml"),
+            vec![
+                "packages/a/pyproject.toml",
+                "packages/b/nested/pyproject.toml",
+                "packages/b/pyproject.toml"
+            ]
+        );
+        assert_eq!(
+            expand("packages/.*/pyproject.toml"),
+            vec!["packages/.hidden/pyproject.toml"]
+        );
+        assert_eq!(expand("packages/[!_a]"), vec!["packages/b"]);
+        assert_eq!(expand("packages/?/setup.cfg"), Vec::<String>::new());
+        assert_eq!(
+            super::expand(Path::new("packages/[a")),
+            Err("`[` in `[a` is not closed".into())
+        );
+    }
+
+    #[test]
+    fn test_is_pattern() {
+        assert!(is_pattern("packages/*/pyproject.toml"));
+        assert!(is_pattern("file?.txt"));
+        assert!(!is_pattern("packages/a/pyproject.toml"));
+    }
+
+    #[test]
+    fn test_has_wildcard() {
+        assert!(has_wildcard("packages/*/pyproject.toml"));
+        assert!(has_wildcard("file?.txt"));
+        assert!(!has_wildcard("app/[slug]/page.tsx"));
+    }
+}
@@ -0,0 +1,934 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{
+    Certificate, NoProxy, Proxy, StatusCode,
+    blocking::{Client, RequestBuilder, Response},
+    header::{
+        AUTHORIZATION, CACHE_CONTROL, COOKIE, ETAG, HeaderMap, HeaderName, HeaderValue,
+        IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LOCATION, PROXY_AUTHORIZATION,
+    },
+    redirect::Policy,
+};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use url::Url;
+
+use crate::uri::{PathError, write_atomically};
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CacheConfig {
+    // no responses are cached without a dir
+    pub(crate) dir: Option<PathBuf>,
+    pub(crate) ttl: Duration,
+    pub(crate) offline: bool,
+}
+
+static CACHE_CONFIG: OnceLock<CacheConfig> = OnceLock::new();
+
+// the responses fetched during this run, so an url is fetched once per run
+static FETCHED: OnceLock<Mutex<HashMap<Url, Option<Vec<u8>>>>> = OnceLock::new();
+
+pub(crate) fn configure_cache(config: CacheConfig) {
+    if CACHE_CONFIG.set(config).is_err() {
+        log::debug!("the http cache is already configured");
+    }
+}
+
+pub(crate) fn cache_config() -> CacheConfig {
+    CACHE_CONFIG.get().cloned().unwrap_or_default()
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ClientConfig {
+    pub(crate) connect_timeout: Duration,
+    pub(crate) read_timeout: Duration,
+    pub(crate) retries: u32,
+    pub(crate) proxy: Option<String>,
+    pub(crate) no_proxy: Option<String>,
+    pub(crate) ca_bundle: Option<PathBuf>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            retries: 2,
+            proxy: None,
+            no_proxy: None,
+            ca_bundle: None,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum ClientError {
+    #[error("CA bundle {path} can not be used ({error})")]
+    CaBundle { path: String, error: String },
+    #[error("proxy {proxy} is not valid ({error})")]
+    Proxy {
+        proxy: String,
+        error: reqwest::Error,
+    },
+    #[error("http client can not be created ({0})")]
+    Build(#[from] reqwest::Error),
+}
+
+struct HttpClient {
+    client: Client,
+    retries: u32,
+    // delay before the first retry, which is doubled for every next retry
+    retry_delay: Duration,
+}
+
+impl ClientConfig {
+    fn build(&self) -> Result<HttpClient, ClientError> {
+        // the redirects are followed by send_following_redirects
+        let mut builder = Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.read_timeout)
+            .redirect(Policy::none());
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|error| ClientError::Proxy {
+                    proxy: proxy.clone(),
+                    error,
+                })?
+                .no_proxy(self.no_proxy.as_deref().and_then(NoProxy::from_string));
+            builder = builder.proxy(proxy);
+        }
+        if let Some(ca_bundle) = &self.ca_bundle {
+            let ca_bundle_error = |error: String| ClientError::CaBundle {
+                path: ca_bundle.to_string_lossy().to_string(),
+                error,
+            };
+            let pem_bundle =
+                std::fs::read(ca_bundle).map_err(|e| ca_bundle_error(e.to_string()))?;
+            let certificates = Certificate::from_pem_bundle(&pem_bundle)
+                .map_err(|e| ca_bundle_error(e.to_string()))?;
+            if certificates.is_empty() {
+                return Err(ca_bundle_error("no certificates are found".into()));
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        Ok(HttpClient {
+            client: builder.build()?,
+            retries: self.retries,
+            retry_delay: Duration::from_secs(1),
+        })
+    }
+}
+
+static CLIENT: OnceLock<HttpClient> = OnceLock::new();
+
+pub(crate) fn configure_client(config: &ClientConfig) -> Result<(), ClientError> {
+    if CLIENT.set(config.build()?).is_err() {
+        log::debug!("the http client is already configured");
+    }
+    Ok(())
+}
+
+fn client() -> &'static HttpClient {
+    CLIENT.get_or_init(|| {
+        ClientConfig::default()
+            .build()
+            .expect("the default http client can be created")
+    })
+}
+
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("CHECK_CONFIG_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::cache_dir().map(|dir| dir.join("check-config"))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+struct CacheEntry {
+    dir: PathBuf,
+}
+
+impl CacheEntry {
+    fn new(cache_dir: &Path, url: &Url) -> Self {
+        Self {
+            dir: cache_dir
+                .join("http")
+                .join(format!("{:x}", Sha256::digest(url.as_str()))),
+        }
+    }
+
+    fn read(&self) -> Option<(Value, Vec<u8>)> {
+        let meta = serde_json::from_slice(&std::fs::read(self.dir.join("meta.json")).ok()?).ok()?;
+        let body = std::fs::read(self.dir.join("body")).ok()?;
+        Some((meta, body))
+    }
+
+    fn is_fresh(meta: &Value, ttl: Duration) -> bool {
+        let fetched_at = meta["fetched_at"].as_u64().unwrap_or_default();
+        now().saturating_sub(fetched_at) < ttl.as_secs()
+    }
+
+    fn write_meta(&self, meta: &Value) -> std::io::Result<()> {
+        write_atomically(
+            &self.dir.join("meta.json"),
+            meta.to_string().as_bytes(),
+            private_permissions(),
+        )
+    }
+
+    fn write(&self, meta: &Value, body: &[u8]) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        write_atomically(&self.dir.join("body"), body, private_permissions())?;
+        self.write_meta(meta)
+    }
+
+    fn remove(&self) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Other users may not read what the current user fetched
+#[cfg(not(target_os = "windows"))]
+fn private_permissions() -> Option<std::fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+
+    Some(std::fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(target_os = "windows")]
+fn private_permissions() -> Option<std::fs::Permissions> {
+    None
+}
+
+/// Whether the request is sent with credentials or extra headers. The response might then be
+/// meant for the current user only, so it is not cached.
+fn is_personal(
+    url: &Url,
+    credentials: Option<&Credentials>,
+    extra_headers: &[ExtraHeader],
+) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    credentials.is_some() || extra_headers.iter().any(|header| header.applies_to(host))
+}
+
+fn is_no_store(response: &Response) -> bool {
+    response
+        .headers()
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-store"))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ExtraHeader {
+    pub(crate) host: Option<String>,
+    pub(crate) name: HeaderName,
+    pub(crate) value: HeaderValue,
+}
+
+impl ExtraHeader {
+    /// A header with credentials needs a host, so it is not sent to every server.
+    pub(crate) fn new(host: Option<&str>, name: &str, value: &str) -> Result<Self, String> {
+        let header = Self {
+            host: host.map(|host| host.to_lowercase()),
+            name: HeaderName::try_from(name.trim())
+                .map_err(|_| format!("`{name}` is not a valid header name"))?,
+            value: HeaderValue::try_from(value.trim())
+                .map_err(|_| format!("the value of header `{name}` is not valid"))?,
+        };
+        if header.host.is_none() && header.is_credential() {
+            return Err(format!(
+                "header `{}` contains credentials, so it needs a host",
+                name.trim()
+            ));
+        }
+        Ok(header)
+    }
+
+    fn is_credential(&self) -> bool {
+        let name = self.name.as_str();
+        [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE].contains(&self.name)
+            || ["token", "secret", "password", "api-key", "apikey"]
+                .iter()
+                .any(|part| name.contains(part))
+    }
+
+    pub(crate) fn parse(header: &str) -> Result<Self, String> {
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(format!("header `{header}` is not formatted as NAME: VALUE"));
+        };
+        match name.split_once('=') {
+            Some((host, name)) => Self::new(Some(host.trim()), name, value),
+            None => Self::new(None, name, value),
+        }
+    }
+
+    fn applies_to(&self, host: &str) -> bool {
+        self.host
+            .as_deref()
+            .is_none_or(|header_host| header_host.eq_ignore_ascii_case(host))
+    }
+}
+
+pub(crate) static EXTRA_HEADERS: Mutex<Vec<ExtraHeader>> = Mutex::new(Vec::new());
+
+/// A header replaces the one with the same name for the same host, as the checkers file is
+/// read again for every project of --each.
+pub(crate) fn add_extra_header(header: ExtraHeader) {
+    let mut extra_headers = EXTRA_HEADERS.lock().expect("lock is not poisoned");
+    extra_headers.retain(|other| other.host != header.host || other.name != header.name);
+    extra_headers.push(header);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Credentials {
+    Bearer(String),
+    Basic { user: String, password: String },
+}
+
+fn host_env_var(prefix: &str, host: &str) -> String {
+    let host = host
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect::<String>();
+    format!("CHECK_CONFIG_{prefix}_{host}")
+}
+
+/// The `default` entry is not used, as its credentials would be sent to every host.
+fn netrc_credentials(netrc: &str, host: &str) -> Option<Credentials> {
+    // machine, login and password of the entries, the machine of the default entry is `None`
+    let mut entries: Vec<(Option<&str>, Option<&str>, Option<&str>)> = vec![];
+    let mut tokens = netrc.split_whitespace();
+    while let Some(token) = tokens.next() {
+        match (token, entries.last_mut()) {
+            ("machine", _) => entries.push((Some(tokens.next().unwrap_or_default()), None, None)),
+            ("default", _) => entries.push((None, None, None)),
+            ("login", Some(entry)) => entry.1 = tokens.next(),
+            ("password", Some(entry)) => entry.2 = tokens.next(),
+            _ => {}
+        }
+    }
+    let (_, user, password) = entries.iter().find(|(machine, _, _)| {
+        machine.is_some_and(|machine| machine.eq_ignore_ascii_case(host))
+    })?;
+    Some(Credentials::Basic {
+        user: (*user)?.to_string(),
+        password: (*password)?.to_string(),
+    })
+}
+
+fn credentials(
+    host: &str,
+    env_var: impl Fn(&str) -> Option<String>,
+    netrc: Option<&str>,
+) -> Option<Credentials> {
+    if let Some(token) = env_var(&host_env_var("TOKEN", host)) {
+        return Some(Credentials::Bearer(token));
+    }
+    if let Some(user) = env_var(&host_env_var("USER", host)) {
+        return Some(Credentials::Basic {
+            user,
+            password: env_var(&host_env_var("PASSWORD", host)).unwrap_or_default(),
+        });
+    }
+    netrc.and_then(|netrc| netrc_credentials(netrc, host))
+}
+
+pub(crate) fn host_credentials(host: &str) -> Option<Credentials> {
+    credentials(
+        host,
+        |name| std::env::var(name).ok(),
+        read_netrc().as_deref(),
+    )
+}
+
+fn read_netrc() -> Option<String> {
+    let path = match std::env::var_os("NETRC") {
+        Some(path) => PathBuf::from(path),
+        None => dirs::home_dir()?.join(".netrc"),
+    };
+    std::fs::read_to_string(path).ok()
+}
+
+/// The credentials, and the extra headers with credentials, are only sent over https.
+fn request(
+    client: &Client,
+    url: &Url,
+    credentials: Option<Credentials>,
+    extra_headers: &[ExtraHeader],
+) -> RequestBuilder {
+    let is_https = url.scheme() == "https";
+    let mut request = client.get(url.clone());
+    request = match credentials {
+        Some(_) if !is_https => {
+            log::warn!("⚠ the credentials for {url} are not sent, as it is not an https url");
+            request
+        }
+        Some(Credentials::Bearer(token)) => request.bearer_auth(token),
+        Some(Credentials::Basic { user, password }) => request.basic_auth(user, Some(password)),
+        None => request,
+    };
+    let host = url.host_str().unwrap_or_default();
+    for header in extra_headers
+        .iter()
+        .filter(|header| header.applies_to(host))
+        .filter(|header| is_https || !header.is_credential())
+    {
+        request = request.header(header.name.clone(), header.value.clone());
+    }
+    request
+}
+
+fn send_with_retries(
+    request: RequestBuilder,
+    retries: u32,
+    retry_delay: Duration,
+) -> reqwest::Result<Response> {
+    let mut attempt = 0;
+    loop {
+        let Some(retryable_request) = request.try_clone().filter(|_| attempt < retries) else {
+            return request.send();
+        };
+        let error = match retryable_request.send() {
+            Ok(response) if response.status().is_server_error() => {
+                format!("{} responded with {}", response.url(), response.status())
+            }
+            Err(e) if e.is_connect() => e.to_string(),
+            result => return result,
+        };
+        let delay = retry_delay * 2u32.pow(attempt);
+        log::warn!("⚠ {error}, retrying in {delay:?}");
+        std::thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
+const MAX_REDIRECTS: usize = 10;
+
+/// Every request gets the credentials and the extra headers of its own host, so they are not
+/// passed on to the host of a redirect. The flag tells whether the last request was personal.
+fn send_following_redirects(
+    client: &HttpClient,
+    url: &Url,
+    credentials: impl Fn(&Url) -> Option<Credentials>,
+    extra_headers: &[ExtraHeader],
+    validators: &HeaderMap,
+) -> reqwest::Result<(Response, bool)> {
+    let mut url = url.clone();
+    let mut redirects = 0;
+    loop {
+        let url_credentials = credentials(&url);
+        let personal = is_personal(&url, url_credentials.as_ref(), extra_headers);
+        let request = request(&client.client, &url, url_credentials, extra_headers)
+            .headers(validators.clone());
+        let response = send_with_retries(request, client.retries, client.retry_delay)?;
+        match redirect_location(&response) {
+            Some(location) if redirects < MAX_REDIRECTS => {
+                url = location;
+                redirects += 1;
+            }
+            _ => return Ok((response, personal)),
+        }
+    }
+}
+
+fn redirect_location(response: &Response) -> Option<Url> {
+    if !response.status().is_redirection() || response.status() == StatusCode::NOT_MODIFIED {
+        return None;
+    }
+    let location = response.headers().get(LOCATION)?.to_str().ok()?;
+    response.url().join(location).ok()
+}
+
+pub(crate) fn fetch(url: &Url) -> Result<Option<Vec<u8>>, PathError> {
+    let fetched = FETCHED.get_or_init(Default::default);
+    if let Some(contents) = fetched.lock().expect("lock is not poisoned").get(url) {
+        return Ok(contents.clone());
+    }
+
+    let extra_headers = EXTRA_HEADERS.lock().expect("lock is not poisoned").clone();
+    let contents = fetch_with_cache(url, &cache_config(), &extra_headers)?;
+    fetched
+        .lock()
+        .expect("lock is not poisoned")
+        .insert(url.clone(), contents.clone());
+    Ok(contents)
+}
+
+fn fetch_with_cache(
+    url: &Url,
+    config: &CacheConfig,
+    extra_headers: &[ExtraHeader],
+) -> Result<Option<Vec<u8>>, PathError> {
+    let credentials = |url: &Url| url.host_str().and_then(host_credentials);
+    let entry = config
+        .dir
+        .as_ref()
+        .filter(|_| !is_personal(url, credentials(url).as_ref(), extra_headers))
+        .map(|dir| CacheEntry::new(dir, url));
+    let cached = entry.as_ref().and_then(|entry| entry.read());
+
+    if config.offline {
+        return match cached {
+            Some((_, body)) => Ok(Some(body)),
+            None => Err(PathError::NotCached(url.to_string())),
+        };
+    }
+    if let Some((meta, body)) = &cached
+        && CacheEntry::is_fresh(meta, config.ttl)
+    {
+        return Ok(Some(body.clone()));
+    }
+
+    let mut validators = HeaderMap::new();
+    if let Some((meta, _)) = &cached {
+        let header_value = |key: &str| HeaderValue::from_str(meta[key].as_str()?).ok();
+        if let Some(etag) = header_value("etag") {
+            validators.insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = header_value("last_modified") {
+            validators.insert(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let (response, personal) =
+        match send_following_redirects(client(), url, credentials, extra_headers, &validators) {
+            Ok(response) => response,
+            Err(e) => {
+                return match cached {
+                    Some((_, body)) => {
+                        log::warn!(
+                            "⚠ {url} can not be fetched ({e}), the cached contents are used"
+                        );
+                        Ok(Some(body))
+                    }
+                    None => Err(e.into()),
+                };
+            }
+        };
+
+    if response.status() == StatusCode::NOT_MODIFIED
+        && let (Some(entry), Some((mut meta, body))) = (&entry, cached)
+    {
+        meta["fetched_at"] = now().into();
+        if let Err(e) = entry.write_meta(&meta) {
+            log::warn!("⚠ {url} can not be cached ({e})");
+        }
+        return Ok(Some(body));
+    }
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+    let meta = json!({
+        "url": url.as_str(),
+        "etag": header(ETAG),
+        "last_modified": header(LAST_MODIFIED),
+        "fetched_at": now(),
+    });
+    let no_store = is_no_store(&response);
+    let body = response.bytes()?.to_vec();
+
+    let cached = match &entry {
+        Some(entry) if no_store || personal => entry.remove(),
+        Some(entry) => entry.write(&meta, &body),
+        None => Ok(()),
+    };
+    if let Err(e) = cached {
+        log::warn!("⚠ {url} can not be cached ({e})");
+    }
+    Ok(Some(body))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread::JoinHandle,
+    };
+
+    use super::*;
+
+    pub(crate) fn serve(responses: Vec<String>) -> (Url, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/file", listener.local_addr().unwrap())).unwrap();
+        let handle = std::thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request = String::new();
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                        request.push_str(&line.to_lowercase());
+                    }
+                    stream.write_all(response.as_bytes()).unwrap();
+                    request
+                })
+                .collect()
+        });
+        (url, handle)
+    }
+
+    pub(crate) fn response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\n{headers}content-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    #[test]
+    fn test_fetch_with_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = CacheConfig {
+            dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let (url, server) = serve(vec![
+            response("200 OK", "etag: \"v1\"\r\n", "contents"),
+            response("304 Not Modified", "", ""),
+            response("404 Not Found", "", ""),
+        ]);
+
+        assert_eq!(
+            fetch_with_cache(&url, &config, &[]).unwrap(),
+            Some(b"contents".to_vec())
+        );
+        // revalidated, as the ttl is 0
+        assert_eq!(
+            fetch_with_cache(&url, &config, &[]).unwrap(),
+            Some(b"contents".to_vec())
+        );
+        let other_url = url.join("other").unwrap();
+        assert_eq!(fetch_with_cache(&other_url, &config, &[]).unwrap(), None);
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+
+        // the server is gone, so the cached response must be used without a request
+        config.ttl = Duration::from_secs(3600);
+        assert_eq!(
+            fetch_with_cache(&url, &config, &[]).unwrap(),
+            Some(b"contents".to_vec())
+        );
+
+        config.offline = true;
+        assert_eq!(
+            fetch_with_cache(&url, &config, &[]).unwrap(),
+            Some(b"contents".to_vec())
+        );
+        assert!(matches!(
+            fetch_with_cache(&other_url, &config, &[]),
+            Err(PathError::NotCached(_))
+        ));
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let entry = CacheEntry::new(dir.path(), &url);
+            for file in ["body", "meta.json"] {
+                let metadata = std::fs::metadata(entry.dir.join(file)).unwrap();
+                assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fetch_with_cache_not_stored() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = CacheConfig {
+            dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let (url, server) = serve(vec![
+            response("200 OK", "", "v1"),
+            response("200 OK", "cache-control: private, no-store\r\n", "v2"),
+            response("200 OK", "", "v3"),
+        ]);
+        let entry_dir = CacheEntry::new(dir.path(), &url).dir;
+
+        assert_eq!(
+            fetch_with_cache(&url, &config, &[]).unwrap(),
+            Some(b"v1".to_vec())
+        );
+        assert!(entry_dir.exists());
+
+        // a response which may not be stored removes the cached response
+        assert_eq!(
+            fetch_with_cache(&url, &config, &[]).unwrap(),
+            Some(b"v2".to_vec())
+        );
+        assert!(!entry_dir.exists());
+
+        // a response to a request with extra headers is not stored
+        assert_eq!(
+            fetch_with_cache(
+                &url,
+                &config,
+                &[ExtraHeader::parse("X-Api-Version: 2").unwrap()]
+            )
+            .unwrap(),
+            Some(b"v3".to_vec())
+        );
+        assert!(!entry_dir.exists());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_is_personal() {
+        let url = Url::parse("https://artifacts.example.com/file").unwrap();
+        let bearer = Credentials::Bearer("token".into());
+        let header = |header| vec![ExtraHeader::parse(header).unwrap()];
+
+        assert!(!is_personal(&url, None, &[]));
+        assert!(is_personal(&url, Some(&bearer), &[]));
+        assert!(is_personal(&url, None, &header("X-Api-Version: 2")));
+        assert!(is_personal(
+            &url,
+            None,
+            &header("artifacts.example.com=X-Api-Version: 2")
+        ));
+        assert!(!is_personal(
+            &url,
+            None,
+            &header("other.example.com=X-Api-Version: 2")
+        ));
+    }
+
+    #[test]
+    fn test_credentials() {
+        let netrc = "machine other.example.com login other password secret\n\
+                     machine artifacts.example.com\n  login user\n  password netrc\n\
+                     default login anonymous password guest";
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        assert_eq!(
+            credentials(
+                "artifacts.example.com",
+                env(&[("CHECK_CONFIG_TOKEN_ARTIFACTS_EXAMPLE_COM", "token")]),
+                Some(netrc)
+            ),
+            Some(Credentials::Bearer("token".into()))
+        );
+        assert_eq!(
+            credentials(
+                "artifacts.example.com",
+                env(&[
+                    ("CHECK_CONFIG_USER_ARTIFACTS_EXAMPLE_COM", "env"),
+                    ("CHECK_CONFIG_PASSWORD_ARTIFACTS_EXAMPLE_COM", "password"),
+                ]),
+                Some(netrc)
+            ),
+            Some(Credentials::Basic {
+                user: "env".into(),
+                password: "password".into()
+            })
+        );
+        assert_eq!(
+            credentials("artifacts.example.com", env(&[]), Some(netrc)),
+            Some(Credentials::Basic {
+                user: "user".into(),
+                password: "netrc".into()
+            })
+        );
+        // the default entry is not used
+        assert_eq!(
+            credentials("unknown.example.com", env(&[]), Some(netrc)),
+            None
+        );
+        assert_eq!(credentials("unknown.example.com", env(&[]), None), None);
+    }
+
+    #[test]
+    fn test_parse_extra_header() {
+        assert_eq!(
+            ExtraHeader::parse("X-Api-Version: 2"),
+            ExtraHeader::new(None, "x-api-version", "2")
+        );
+        assert_eq!(
+            ExtraHeader::parse("Artifacts.example.com=X-Api-Version: a=b"),
+            ExtraHeader::new(Some("artifacts.example.com"), "X-Api-Version", "a=b")
+        );
+        assert_eq!(
+            ExtraHeader::parse("X-Api-Version"),
+            Err("header `X-Api-Version` is not formatted as NAME: VALUE".into())
+        );
+        assert_eq!(
+            ExtraHeader::parse("X Api: 2"),
+            Err("`X Api` is not a valid header name".into())
+        );
+        assert_eq!(
+            ExtraHeader::parse("Authorization: Bearer secret"),
+            Err("header `Authorization` contains credentials, so it needs a host".into())
+        );
+        assert!(ExtraHeader::parse("example.com=X-Api-Token: secret").is_ok());
+    }
+
+    #[test]
+    fn test_request_with_credentials_and_headers() {
+        let (url, server) = serve(vec![response("200 OK", "", "contents")]);
+        let headers = vec![
+            ExtraHeader::parse("X-All: 1").unwrap(),
+            ExtraHeader::parse("127.0.0.1=X-Local: 2").unwrap(),
+            ExtraHeader::parse("example.com=X-Other: 3").unwrap(),
+            ExtraHeader::parse("127.0.0.1=X-Api-Token: 4").unwrap(),
+        ];
+
+        let response = request(
+            &Client::new(),
+            &url,
+            Some(Credentials::Bearer("secret".into())),
+            &headers,
+        )
+        .send()
+        .unwrap();
+        assert_eq!(response.text().unwrap(), "contents");
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].contains("x-all: 1"));
+        assert!(requests[0].contains("x-local: 2"));
+        assert!(!requests[0].contains("x-other"));
+        // the credentials are not sent over http
+        assert!(!requests[0].contains("authorization"));
+        assert!(!requests[0].contains("x-api-token"));
+
+        let https_request = request(
+            &Client::new(),
+            &Url::parse("https://127.0.0.1/check-config.toml").unwrap(),
+            Some(Credentials::Bearer("secret".into())),
+            &headers,
+        )
+        .build()
+        .unwrap();
+        assert_eq!(https_request.headers()["authorization"], "Bearer secret");
+        assert_eq!(https_request.headers()["x-api-token"], "4");
+    }
+
+    #[test]
+    fn test_redirect_to_other_host() {
+        let (other_url, other_server) = serve(vec![response("200 OK", "", "contents")]);
+        let mut other_url = other_url.join("redirected").unwrap();
+        other_url.set_host(Some("localhost")).unwrap();
+        let (url, server) = serve(vec![response(
+            "302 Found",
+            &format!("location: {other_url}\r\n"),
+            "",
+        )]);
+        let headers = vec![
+            ExtraHeader::parse("X-All: 1").unwrap(),
+            ExtraHeader::parse("127.0.0.1=X-Local: 2").unwrap(),
+        ];
+
+        let (response, personal) = send_following_redirects(
+            &ClientConfig::default().build().unwrap(),
+            &url,
+            |url| {
+                (url.host_str() == Some("127.0.0.1")).then(|| Credentials::Bearer("secret".into()))
+            },
+            &headers,
+            &HeaderMap::new(),
+        )
+        .unwrap();
+        assert_eq!(response.url(), &other_url);
+        assert_eq!(response.text().unwrap(), "contents");
+        assert!(personal);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].contains("x-local: 2"));
+        // the headers of the first host are not sent to the other host
+        let other_requests = other_server.join().unwrap();
+        assert!(other_requests[0].contains("get /redirected"));
+        assert!(other_requests[0].contains("x-all: 1"));
+        assert!(!other_requests[0].contains("x-local"));
+        assert!(!other_requests[0].contains("authorization"));
+    }
+
+    #[test]
+    fn test_send_with_retries() {
+        let (url, server) = serve(vec![
+            response("503 Service Unavailable", "", ""),
+            response("200 OK", "", "contents"),
+            response("502 Bad Gateway", "", ""),
+            response("500 Internal Server Error", "", ""),
+        ]);
+        let client = Client::new();
+
+        let response = send_with_retries(client.get(url.clone()), 2, Duration::ZERO).unwrap();
+        assert_eq!(response.text().unwrap(), "contents");
+        let response = send_with_retries(client.get(url.clone()), 1, Duration::ZERO).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(server.join().unwrap().len(), 4);
+
+        // nothing listens on the port anymore
+        let e = send_with_retries(client.get(url), 2, Duration::ZERO).unwrap_err();
+        assert!(e.is_connect());
+    }
+
+    #[test]
+    fn test_client_with_invalid_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let ca_bundle = dir.path().join("ca.pem");
+        std::fs::write(&ca_bundle, "no certificate").unwrap();
+
+        let config = ClientConfig {
+            ca_bundle: Some(ca_bundle.clone()),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.build().err().unwrap().to_string(),
+            format!(
+                "CA bundle {} can not be used (no certificates are found)",
+                ca_bundle.to_string_lossy()
+            )
+        );
+
+        let config = ClientConfig {
+            proxy: Some("not a url".into()),
+            ..Default::default()
+        };
+        assert!(matches!(config.build(), Err(ClientError::Proxy { .. })));
+    }
+}
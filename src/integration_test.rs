@@ -21,6 +21,7 @@ mod tests {
             &file_with_checks,
             &mut variables,
         )
+        .checks
         .into_iter()
         .filter(|c| {
             cli::filter_checks(
@@ -32,7 +33,10 @@ mod tests {
         })
         .collect();
 
-        assert_eq!(cli::run_checks(&checks, true), cli::ExitStatus::Success);
+        assert_eq!(
+            cli::run_checks(&checks, true, &mut None).exit_status(),
+            cli::ExitStatus::Success
+        );
 
         assert!(!dir_diff::is_different("output", "example/expected_output").unwrap());
 
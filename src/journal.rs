@@ -0,0 +1,664 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::uri;
+
+const JOURNAL_FILE: &str = "journal.json";
+
+#[derive(Error, Debug)]
+pub(crate) enum JournalError {
+    #[error("io error ({0})")]
+    Io(#[from] io::Error),
+    #[error("invalid journal {0}")]
+    InvalidJournal(String),
+    #[error("run {0} not found")]
+    RunNotFound(String),
+    #[error("no run found to undo")]
+    NoRunToUndo,
+    #[error("run {0} is already undone")]
+    AlreadyUndone(String),
+    #[error("no state dir found to store the journal")]
+    NoStateDir,
+    #[error("{0} changed since the fix, use --force to undo anyway")]
+    ChangedSinceFix(String),
+}
+
+pub(crate) fn state_dir() -> Option<PathBuf> {
+    if let Ok(state_dir) = std::env::var("CHECK_CONFIG_STATE_DIR") {
+        return Some(PathBuf::from(state_dir));
+    }
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("check-config"))
+}
+
+fn runs_dir(state_dir: &Path) -> PathBuf {
+    state_dir.join("runs")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Backup {
+    /// The path did not exist, so it is removed on undo
+    Absent,
+    File {
+        backup: PathBuf,
+    },
+    Symlink {
+        target: PathBuf,
+    },
+    Dir {
+        backup: PathBuf,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct JournalEntry {
+    path: PathBuf,
+    backup: Backup,
+    // fingerprint of the path after the fixes, to detect changes made after the run
+    fixed: Option<String>,
+}
+
+impl JournalEntry {
+    fn to_json(&self) -> Value {
+        let path = self.path.to_string_lossy();
+        let mut value = match &self.backup {
+            Backup::Absent => json!({"path": path, "kind": "absent"}),
+            Backup::File { backup } => {
+                json!({"path": path, "kind": "file", "backup": backup.to_string_lossy()})
+            }
+            Backup::Symlink { target } => {
+                json!({"path": path, "kind": "symlink", "target": target.to_string_lossy()})
+            }
+            Backup::Dir { backup } => {
+                json!({"path": path, "kind": "dir", "backup": backup.to_string_lossy()})
+            }
+        };
+        if let Some(fixed) = &self.fixed {
+            value["fixed"] = fixed.as_str().into();
+        }
+        value
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let path_of = |key: &str| value.get(key)?.as_str().map(PathBuf::from);
+        let backup = match value.get("kind")?.as_str()? {
+            "absent" => Backup::Absent,
+            "file" => Backup::File {
+                backup: path_of("backup")?,
+            },
+            "symlink" => Backup::Symlink {
+                target: path_of("target")?,
+            },
+            "dir" => Backup::Dir {
+                backup: path_of("backup")?,
+            },
+            _ => return None,
+        };
+        Some(Self {
+            path: path_of("path")?,
+            backup,
+            // journals of older versions have no fingerprint
+            fixed: value
+                .get("fixed")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        })
+    }
+
+    fn is_changed_since_fix(&self) -> bool {
+        self.fixed
+            .as_ref()
+            .is_some_and(|fixed| fingerprint(&self.path).ok().as_ref() != Some(fixed))
+    }
+
+    fn restore(&self) -> Result<(), JournalError> {
+        remove_path(&self.path)?;
+        if let Some(parent) = self.path.parent()
+            && !matches!(self.backup, Backup::Absent)
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        match &self.backup {
+            Backup::Absent => (),
+            Backup::File { backup } | Backup::Dir { backup } => copy_path(backup, &self.path)?,
+            Backup::Symlink { target } => create_symlink(target, &self.path)?,
+        }
+        Ok(())
+    }
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Err(_) => Ok(()),
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(target_os = "windows")]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(target, path)
+}
+
+fn fingerprint(path: &Path) -> io::Result<String> {
+    fn digest(path: &Path, hasher: &mut Sha256) -> io::Result<()> {
+        match fs::symlink_metadata(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => hasher.update(b"absent"),
+            Err(e) => return Err(e),
+            Ok(metadata) if metadata.is_symlink() => {
+                hasher.update(b"symlink:");
+                hasher.update(fs::read_link(path)?.to_string_lossy().as_bytes());
+            }
+            Ok(metadata) if metadata.is_dir() => {
+                hasher.update(b"dir:");
+                let mut names = fs::read_dir(path)?
+                    .map(|entry| entry.map(|entry| entry.file_name()))
+                    .collect::<io::Result<Vec<_>>>()?;
+                names.sort();
+                for name in names {
+                    hasher.update(name.to_string_lossy().as_bytes());
+                    hasher.update(b"\0");
+                    digest(&path.join(name), hasher)?;
+                }
+                hasher.update(b"\0");
+            }
+            Ok(_) => {
+                let contents = fs::read(path)?;
+                hasher.update(format!("file:{}:", contents.len()).as_bytes());
+                hasher.update(contents);
+            }
+        }
+        Ok(())
+    }
+
+    let mut hasher = Sha256::new();
+    digest(path, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn copy_path(src: &Path, dst: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(src)?;
+    if metadata.is_symlink() {
+        create_symlink(&fs::read_link(src)?, dst)
+    } else if metadata.is_dir() {
+        fs::create_dir(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_path(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+        // set the permissions after the contents are copied, as they can make the dir read only
+        fs::set_permissions(dst, metadata.permissions())
+    } else {
+        fs::copy(src, dst).map(|_| ())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Journal {
+    pub(crate) id: String,
+    dir: PathBuf,
+    pub(crate) file_with_checks: String,
+    pub(crate) undone: bool,
+    entries: Vec<JournalEntry>,
+    // backups of the checker which is currently fixed
+    pending: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// The id is sortable in the order in which the runs are started: the nanoseconds since the
+    /// epoch and a counter for runs started in the same nanosecond.
+    pub(crate) fn create(state_dir: &Path, file_with_checks: &str) -> Result<Self, JournalError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        let runs_dir = runs_dir(state_dir);
+        fs::create_dir_all(&runs_dir)?;
+        let (id, dir) = (0..)
+            .map(|counter| format!("{timestamp:020}-{counter:03}"))
+            .map(|id| (runs_dir.join(&id), id))
+            .find_map(|(dir, id)| match fs::create_dir(&dir) {
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => None,
+                result => Some(result.map(|_| (id, dir))),
+            })
+            .expect("a free id is found")?;
+
+        Ok(Self {
+            id,
+            dir,
+            file_with_checks: file_with_checks.to_string(),
+            undone: false,
+            entries: vec![],
+            pending: vec![],
+        })
+    }
+
+    fn load(dir: &Path) -> Result<Self, JournalError> {
+        let id = dir
+            .file_name()
+            .map(|id| id.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let invalid = || JournalError::InvalidJournal(id.clone());
+
+        let value: Value = serde_json::from_str(&fs::read_to_string(dir.join(JOURNAL_FILE))?)
+            .map_err(|_| invalid())?;
+        let entries = value
+            .get("entries")
+            .and_then(Value::as_array)
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|entry| JournalEntry::from_json(entry).ok_or_else(invalid))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            id: id.clone(),
+            dir: dir.to_path_buf(),
+            file_with_checks: value
+                .get("file_with_checks")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            undone: value
+                .get("undone")
+                .and_then(Value::as_bool)
+                .unwrap_or_default(),
+            entries,
+            pending: vec![],
+        })
+    }
+
+    pub(crate) fn list(state_dir: &Path) -> Result<Vec<Self>, JournalError> {
+        let runs_dir = runs_dir(state_dir);
+        if !runs_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut journals = vec![];
+        for entry in fs::read_dir(runs_dir)?.flatten() {
+            if !entry.path().join(JOURNAL_FILE).exists() {
+                continue;
+            }
+            match Self::load(&entry.path()) {
+                Ok(journal) => journals.push(journal),
+                Err(e) => log::warn!(
+                    "⚠ journal {} is skipped, as it can not be read ({e})",
+                    entry.path().to_string_lossy()
+                ),
+            }
+        }
+        journals.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(journals)
+    }
+
+    pub(crate) fn find(state_dir: &Path, id: Option<&str>) -> Result<Self, JournalError> {
+        let journals = Self::list(state_dir)?;
+        match id {
+            Some(id) => journals
+                .into_iter()
+                .find(|journal| journal.id == id)
+                .ok_or_else(|| JournalError::RunNotFound(id.to_string())),
+            None => journals
+                .into_iter()
+                .rev()
+                .find(|journal| !journal.undone)
+                .ok_or(JournalError::NoRunToUndo),
+        }
+    }
+
+    pub(crate) fn paths(&self) -> Vec<&Path> {
+        self.entries
+            .iter()
+            .map(|entry| entry.path.as_path())
+            .collect()
+    }
+
+    fn is_recorded(&self, path: &Path) -> bool {
+        self.entries
+            .iter()
+            .chain(self.pending.iter())
+            .any(|entry| entry.path == path)
+    }
+
+    /// The backup is pending until it is committed.
+    pub(crate) fn backup(&mut self, path: &Path) -> Result<(), JournalError> {
+        // when a path does not exist, the fix might create its missing parent dirs as well
+        let mut path = path;
+        while !path.exists()
+            && fs::symlink_metadata(path).is_err()
+            && let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            path = parent;
+        }
+
+        if self.is_recorded(path) {
+            return Ok(());
+        }
+
+        let backup_path = self
+            .dir
+            .join(format!("{}", self.entries.len() + self.pending.len()));
+        let backup = match fs::symlink_metadata(path) {
+            Err(_) => Backup::Absent,
+            Ok(metadata) if metadata.is_symlink() => Backup::Symlink {
+                target: fs::read_link(path)?,
+            },
+            Ok(metadata) if metadata.is_dir() => {
+                copy_path(path, &backup_path)?;
+                Backup::Dir {
+                    backup: backup_path,
+                }
+            }
+            Ok(_) => {
+                copy_path(path, &backup_path)?;
+                Backup::File {
+                    backup: backup_path,
+                }
+            }
+        };
+        let is_symlink = matches!(backup, Backup::Symlink { .. });
+        self.pending.push(JournalEntry {
+            path: path.to_path_buf(),
+            backup,
+            fixed: None,
+        });
+
+        // a fix writes through a symlink, so the target is recorded as well
+        if is_symlink && let Ok(target) = fs::canonicalize(path) {
+            self.backup(&target)?;
+        }
+        Ok(())
+    }
+
+    /// The journal is written right away, so an interrupted run can be undone as well.
+    pub(crate) fn commit(&mut self) -> Result<(), JournalError> {
+        self.entries.append(&mut self.pending);
+        // a fix can change a path which was recorded by the fix of another checker
+        for entry in self.entries.iter_mut() {
+            entry.fixed = fingerprint(&entry.path).ok();
+        }
+        self.write()
+    }
+
+    pub(crate) fn discard(&mut self) {
+        for entry in self.pending.drain(..) {
+            if let Backup::File { backup } | Backup::Dir { backup } = entry.backup {
+                let _ = remove_path(&backup);
+            }
+        }
+    }
+
+    pub(crate) fn save(&mut self) -> Result<(), JournalError> {
+        self.discard();
+        if self.entries.is_empty() {
+            fs::remove_dir_all(&self.dir)?;
+            return Ok(());
+        }
+        self.write()
+    }
+
+    fn write(&self) -> Result<(), JournalError> {
+        let value = json!({
+            "id": self.id,
+            "file_with_checks": self.file_with_checks,
+            "undone": self.undone,
+            "entries": self.entries.iter().map(JournalEntry::to_json).collect::<Vec<_>>(),
+        });
+        uri::write_atomically(
+            &self.dir.join(JOURNAL_FILE),
+            serde_json::to_string_pretty(&value)
+                .expect("journal can be serialized")
+                .as_bytes(),
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// Without force, nothing is restored when a path is changed after the run.
+    pub(crate) fn undo(&mut self, force: bool) -> Result<(), JournalError> {
+        if self.undone {
+            return Err(JournalError::AlreadyUndone(self.id.clone()));
+        }
+
+        let changed_paths = self
+            .entries
+            .iter()
+            .filter(|entry| entry.is_changed_since_fix())
+            .map(|entry| entry.path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        if !changed_paths.is_empty() {
+            if !force {
+                return Err(JournalError::ChangedSinceFix(changed_paths.join(", ")));
+            }
+            for path in changed_paths {
+                log::warn!("⚠ {path} changed since the fix, the changes are overwritten");
+            }
+        }
+
+        for entry in self.entries.iter().rev() {
+            entry.restore()?;
+        }
+
+        self.undone = true;
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let changed_file = dir.path().join("changed");
+        let removed_dir = dir.path().join("removed");
+        let created_file = dir.path().join("new_dir").join("created");
+        let untouched_file = dir.path().join("untouched");
+        fs::write(&changed_file, "old").unwrap();
+        fs::create_dir(&removed_dir).unwrap();
+        fs::write(removed_dir.join("file"), "file in dir").unwrap();
+        fs::write(&untouched_file, "untouched").unwrap();
+
+        let mut journal = Journal::create(state_dir.path(), "check-config.toml").unwrap();
+        journal.backup(&changed_file).unwrap();
+        journal.backup(&removed_dir).unwrap();
+        fs::write(&changed_file, "new").unwrap();
+        fs::remove_dir_all(&removed_dir).unwrap();
+        journal.commit().unwrap();
+
+        journal.backup(&created_file).unwrap();
+        fs::create_dir(dir.path().join("new_dir")).unwrap();
+        fs::write(&created_file, "created").unwrap();
+        journal.commit().unwrap();
+
+        // the second backup of a path is ignored, the state before the run is kept
+        journal.backup(&changed_file).unwrap();
+        journal.backup(&untouched_file).unwrap();
+        journal.discard();
+        journal.save().unwrap();
+
+        let mut journal = Journal::find(state_dir.path(), None).unwrap();
+        assert_eq!(
+            journal.paths(),
+            vec![
+                changed_file.as_path(),
+                removed_dir.as_path(),
+                dir.path().join("new_dir").as_path()
+            ]
+        );
+        journal.undo(false).unwrap();
+
+        assert_eq!(fs::read_to_string(&changed_file).unwrap(), "old");
+        assert_eq!(
+            fs::read_to_string(removed_dir.join("file")).unwrap(),
+            "file in dir"
+        );
+        assert!(!dir.path().join("new_dir").exists());
+        assert_eq!(fs::read_to_string(&untouched_file).unwrap(), "untouched");
+
+        // a run can be undone once
+        assert!(matches!(
+            Journal::find(state_dir.path(), None),
+            Err(JournalError::NoRunToUndo)
+        ));
+        let id = journal.id.clone();
+        assert!(matches!(
+            Journal::find(state_dir.path(), Some(&id))
+                .unwrap()
+                .undo(false),
+            Err(JournalError::AlreadyUndone(_))
+        ));
+    }
+
+    #[test]
+    fn test_runs_are_listed_in_order_and_unreadable_runs_are_skipped() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+
+        let ids = (0..3)
+            .map(|_| {
+                let mut journal = Journal::create(state_dir.path(), "check-config.toml").unwrap();
+                journal.backup(&file).unwrap();
+                journal.commit().unwrap();
+                journal.save().unwrap();
+                journal.id
+            })
+            .collect::<Vec<_>>();
+        fs::create_dir(runs_dir(state_dir.path()).join("corrupt")).unwrap();
+        fs::write(
+            runs_dir(state_dir.path())
+                .join("corrupt")
+                .join(JOURNAL_FILE),
+            "{",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Journal::list(state_dir.path())
+                .unwrap()
+                .into_iter()
+                .map(|journal| journal.id)
+                .collect::<Vec<_>>(),
+            ids
+        );
+        assert_eq!(Journal::find(state_dir.path(), None).unwrap().id, ids[2]);
+    }
+
+    #[test]
+    fn test_interrupted_run_can_be_undone() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        fs::write(&file, "old").unwrap();
+
+        let mut journal = Journal::create(state_dir.path(), "check-config.toml").unwrap();
+        journal.backup(&file).unwrap();
+        fs::write(&file, "new").unwrap();
+        journal.commit().unwrap();
+        // the run stops before the journal is saved
+        drop(journal);
+
+        Journal::find(state_dir.path(), None)
+            .unwrap()
+            .undo(false)
+            .unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "old");
+    }
+
+    #[test]
+    fn test_undo_refuses_paths_changed_since_the_fix() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        let other_file = dir.path().join("other_file");
+        fs::write(&file, "old").unwrap();
+
+        let mut journal = Journal::create(state_dir.path(), "check-config.toml").unwrap();
+        journal.backup(&file).unwrap();
+        journal.backup(&other_file).unwrap();
+        fs::write(&file, "fixed").unwrap();
+        fs::write(&other_file, "fixed").unwrap();
+        journal.commit().unwrap();
+        journal.save().unwrap();
+        fs::write(&file, "edited").unwrap();
+
+        let mut journal = Journal::find(state_dir.path(), None).unwrap();
+        assert!(matches!(
+            journal.undo(false),
+            Err(JournalError::ChangedSinceFix(path)) if path == file.to_string_lossy()
+        ));
+        // nothing is restored
+        assert_eq!(fs::read_to_string(&file).unwrap(), "edited");
+        assert!(other_file.exists());
+
+        journal.undo(true).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "old");
+        assert!(!other_file.exists());
+    }
+
+    #[test]
+    fn test_run_without_changes_is_not_saved() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        fs::write(&file, "contents").unwrap();
+
+        let mut journal = Journal::create(state_dir.path(), "check-config.toml").unwrap();
+        journal.backup(&file).unwrap();
+        journal.discard();
+        journal.save().unwrap();
+
+        assert!(Journal::list(state_dir.path()).unwrap().is_empty());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_undo_restores_symlink_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let state_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        fs::write(&target, "target").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        create_symlink(&target, &link).unwrap();
+
+        let mut journal = Journal::create(state_dir.path(), "check-config.toml").unwrap();
+        journal.backup(&link).unwrap();
+        fs::remove_file(&link).unwrap();
+        fs::write(&target, "changed").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o644)).unwrap();
+        journal.commit().unwrap();
+        journal.save().unwrap();
+
+        Journal::find(state_dir.path(), None)
+            .unwrap()
+            .undo(false)
+            .unwrap();
+
+        assert_eq!(fs::read_link(&link).unwrap(), target);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "target");
+        assert_eq!(
+            fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+}
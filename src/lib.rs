@@ -1,6 +1,11 @@
 mod checkers;
 pub mod cli;
 mod file_types;
+mod git;
+mod glob;
+mod http;
 mod integration_test;
+mod journal;
 mod mapping;
+mod python;
 pub mod uri;
@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Mutex, OnceLock},
+};
+
+use thiserror::Error;
+
+use crate::uri;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PythonError {
+    #[error("no python interpreter is found, give one with --python or CHECK_CONFIG_PYTHON")]
+    NoInterpreter,
+    #[error("python {interpreter} can not be executed ({error})")]
+    CanNotBeExecuted { interpreter: String, error: String },
+    #[error("{0} is not a valid python module name")]
+    InvalidModuleName(String),
+    #[error("{module} is not a python package or module of {interpreter}")]
+    NotFound { module: String, interpreter: String },
+}
+
+// prints the dir of the package or module given as argument. A namespace package and a module
+// without `__file__` have no `__file__`, so their spec is used.
+const FIND_PACKAGE_DIR: &str = r#"
+import importlib.util, os, sys
+spec = importlib.util.find_spec(sys.argv[1])
+if spec is None:
+    sys.exit(3)
+locations = list(spec.submodule_search_locations or [])
+if locations:
+    print(locations[0])
+elif spec.origin and os.path.isabs(spec.origin):
+    print(os.path.dirname(spec.origin))
+else:
+    sys.exit(3)
+"#;
+
+static CONFIGURED_INTERPRETER: OnceLock<Option<PathBuf>> = OnceLock::new();
+// the interpreter per base dir, as a project dir of --each may have its own virtualenv
+static INTERPRETERS: OnceLock<Mutex<HashMap<PathBuf, Result<PathBuf, PythonError>>>> =
+    OnceLock::new();
+
+// a package, or module, resolved in a base dir
+type PackageKey = (PathBuf, String);
+
+// the resolved package dirs of this run per base dir, so python is executed once per package
+static PACKAGE_DIRS: OnceLock<Mutex<HashMap<PackageKey, Result<PathBuf, PythonError>>>> =
+    OnceLock::new();
+
+pub(crate) fn configure_interpreter(interpreter: Option<PathBuf>) {
+    if CONFIGURED_INTERPRETER.set(interpreter).is_err() {
+        log::debug!("the python interpreter is already configured");
+    }
+}
+
+fn venv_python(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts").join("python.exe")
+    } else {
+        venv.join("bin").join("python")
+    }
+}
+
+fn uv_python(dir: &Path) -> Option<PathBuf> {
+    let output = Command::new("uv")
+        .args(["python", "find"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    let path = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !path.trim().is_empty()).then(|| PathBuf::from(path.trim()))
+}
+
+fn detect_interpreter(
+    configured: Option<&Path>,
+    virtual_env: Option<&Path>,
+    dir: &Path,
+    uv_python: impl FnOnce() -> Option<PathBuf>,
+) -> Result<PathBuf, PythonError> {
+    if let Some(configured) = configured {
+        return Ok(configured.to_path_buf());
+    }
+    if let Some(virtual_env) = virtual_env {
+        return Ok(venv_python(virtual_env));
+    }
+    if let Some(python) = dir
+        .ancestors()
+        .map(|dir| venv_python(&dir.join(".venv")))
+        .find(|python| python.is_file())
+    {
+        return Ok(python);
+    }
+    if let Some(python) = uv_python() {
+        return Ok(python);
+    }
+    ["python3", "python"]
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|python| Command::new(python).arg("--version").output().is_ok())
+        .ok_or(PythonError::NoInterpreter)
+}
+
+fn interpreter(dir: &Path) -> Result<PathBuf, PythonError> {
+    INTERPRETERS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("lock is not poisoned")
+        .entry(dir.to_path_buf())
+        .or_insert_with(|| {
+            let interpreter = detect_interpreter(
+                CONFIGURED_INTERPRETER.get().cloned().flatten().as_deref(),
+                std::env::var_os("VIRTUAL_ENV")
+                    .map(PathBuf::from)
+                    .as_deref(),
+                dir,
+                || uv_python(dir),
+            );
+            if let Ok(interpreter) = &interpreter {
+                log::debug!(
+                    "python packages are resolved with {}",
+                    interpreter.display()
+                );
+            }
+            interpreter
+        })
+        .clone()
+}
+
+fn find_package_dir(interpreter: &Path, dir: &Path, module: &str) -> Result<PathBuf, PythonError> {
+    if module.is_empty()
+        || !module
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    {
+        return Err(PythonError::InvalidModuleName(module.to_string()));
+    }
+
+    let output = Command::new(interpreter)
+        .args(["-c", FIND_PACKAGE_DIR, module])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| PythonError::CanNotBeExecuted {
+            interpreter: interpreter.to_string_lossy().to_string(),
+            error: e.to_string(),
+        })?;
+    let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || dir.is_empty() {
+        log::debug!(
+            "python can not find {module}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Err(PythonError::NotFound {
+            module: module.to_string(),
+            interpreter: interpreter.to_string_lossy().to_string(),
+        });
+    }
+    Ok(PathBuf::from(dir))
+}
+
+pub(crate) fn package_dir(module: &str) -> Result<PathBuf, PythonError> {
+    let dir = uri::base_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let key = (dir, module.to_string());
+    let package_dirs = PACKAGE_DIRS.get_or_init(Default::default);
+    if let Some(package_dir) = package_dirs.lock().expect("lock is not poisoned").get(&key) {
+        return package_dir.clone();
+    }
+
+    let package_dir =
+        interpreter(&key.0).and_then(|interpreter| find_package_dir(&interpreter, &key.0, module));
+    package_dirs
+        .lock()
+        .expect("lock is not poisoned")
+        .insert(key, package_dir.clone());
+    package_dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_interpreter() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        let nested = project.join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        let venv_python = venv_python(&project.join(".venv"));
+        std::fs::create_dir_all(venv_python.parent().unwrap()).unwrap();
+        std::fs::write(&venv_python, "").unwrap();
+        let no_uv = || None;
+
+        assert_eq!(
+            detect_interpreter(
+                Some(Path::new("/usr/bin/python3.12")),
+                Some(Path::new("/venv")),
+                &nested,
+                no_uv
+            ),
+            Ok(PathBuf::from("/usr/bin/python3.12"))
+        );
+        assert_eq!(
+            detect_interpreter(None, Some(Path::new("/venv")), &nested, no_uv),
+            Ok(super::venv_python(Path::new("/venv")))
+        );
+        assert_eq!(
+            detect_interpreter(None, None, &nested, no_uv),
+            Ok(venv_python)
+        );
+        assert_eq!(
+            detect_interpreter(None, None, dir.path(), || Some(PathBuf::from("/uv/python"))),
+            Ok(PathBuf::from("/uv/python"))
+        );
+    }
+
+    #[test]
+    fn test_find_package_dir() {
+        let Ok(python) = detect_interpreter(None, None, Path::new("/"), || None) else {
+            // python is not installed
+            return;
+        };
+        let dir = tempfile::tempdir().unwrap();
+        // a namespace package, which has no __init__.py
+        std::fs::create_dir_all(dir.path().join("company").join("config")).unwrap();
+        std::fs::write(dir.path().join("module_without_package.py"), "").unwrap();
+        let find = |module| {
+            let output = Command::new(&python)
+                .env("PYTHONPATH", dir.path())
+                .args(["-c", FIND_PACKAGE_DIR, module])
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+
+        assert_eq!(
+            PathBuf::from(find("company.config")),
+            dir.path().join("company").join("config")
+        );
+        assert_eq!(PathBuf::from(find("module_without_package")), dir.path());
+        assert_eq!(find("sys"), "");
+
+        // the modules in the dir can be imported, as the interpreter is executed in the dir
+        assert_eq!(
+            find_package_dir(&python, dir.path(), "module_without_package"),
+            Ok(dir.path().to_path_buf())
+        );
+        assert_eq!(
+            find_package_dir(&python, dir.path(), "unknown_package_of_check_config"),
+            Err(PythonError::NotFound {
+                module: "unknown_package_of_check_config".into(),
+                interpreter: python.to_string_lossy().to_string(),
+            })
+        );
+        assert_eq!(
+            find_package_dir(&python, dir.path(), "os; import sys"),
+            Err(PythonError::InvalidModuleName("os; import sys".into()))
+        );
+        assert!(matches!(
+            find_package_dir(Path::new("/nonexistent/python"), dir.path(), "os"),
+            Err(PythonError::CanNotBeExecuted { .. })
+        ));
+    }
+
+    #[test]
+    fn test_package_dir() {
+        if interpreter(&uri::base_dir().unwrap_or_default()).is_err() {
+            // python is not installed
+            return;
+        }
+
+        let json_dir = package_dir("json").unwrap();
+        assert!(json_dir.join("__init__.py").is_file());
+        assert_eq!(package_dir("json"), Ok(json_dir));
+        assert!(matches!(
+            package_dir("unknown_package_of_check_config"),
+            Err(PythonError::NotFound { .. })
+        ));
+    }
+}
@@ -1,18 +1,48 @@
 use derive_more::{AsRef, Display, From};
 use dirs;
 use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fs::Permissions,
     hash::{Hash, Hasher},
     io::Write,
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
 };
 use url::Url;
 
+use crate::{git, http, python};
+
+thread_local! {
+    // the dir to which relative paths are resolved during a run, see `with_base_dir`
+    static BASE_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn base_dir() -> std::io::Result<PathBuf> {
+    match BASE_DIR.with_borrow(Clone::clone) {
+        Some(dir) => Ok(dir),
+        None => std::env::current_dir(),
+    }
+}
+
+pub(crate) fn with_base_dir<T>(dir: &Path, run: impl FnOnce() -> T) -> T {
+    let previous = BASE_DIR.replace(Some(dir.to_path_buf()));
+    let result = run();
+    BASE_DIR.set(previous);
+    result
+}
+
 #[derive(Debug, From, Display)]
 pub enum Error {
     InvalidUrl,
     UnknownUrlScheme,
     NoValidPythonURL,
     #[from]
+    Python(python::PythonError),
+    NoValidNodeURL,
+    NoValidCargoURL,
+    #[from]
     IO(std::io::Error),
     #[from]
     Parse(url::ParseError),
@@ -51,6 +81,26 @@ pub enum PathError {
 
     #[error("content is not a string")]
     ContentIsNoString,
+
+    #[error("{0} is not found")]
+    NotFound(String),
+
+    #[error("git error: {0}")]
+    Git(#[from] git2::Error),
+
+    #[error("{0} is not a git url like git+<repo url>[@<ref>]#<path>")]
+    InvalidGitUrl(String),
+
+    #[error("{0} is not cached and can not be fetched offline")]
+    NotCached(String),
+
+    #[error("{algorithm} of {path} is {actual}, but {expected} is expected")]
+    ChecksumMismatch {
+        path: String,
+        algorithm: &'static str,
+        expected: String,
+        actual: String,
+    },
 }
 
 #[derive(AsRef, Clone, Debug, Display)]
@@ -93,6 +143,9 @@ impl ReadablePath {
     /// - https://<path> - uri
     /// - file://<path>  - absolute path
     /// - py://<package>/<path>
+    /// - node://<package>/<path> - path in a package in node_modules
+    /// - cargo://<crate>/<path>  - path in a crate of the cargo workspace or its dependencies
+    /// - git+<repo url>[@<ref>]#<path> - path in a git repo at a branch, tag or commit
     /// - ~/<path>       - relative to home dir
     /// - <path>         - relative to cwd
     /// - /<path>        - absolute path
@@ -105,12 +158,12 @@ impl ReadablePath {
             && input.starts_with("config:")
         {
             let input = input.replacen("config:", "", 1);
-            return Ok(ReadablePath::from_url(
-                config_file_path
-                    .as_ref()
-                    .join(input.as_str())
-                    .map_err(|_e| Error::InvalidUrl)?,
-            ));
+            let config_url = config_file_path.as_ref();
+            let url = match config_url.scheme().starts_with("git+") {
+                true => git::join(config_url, input.as_str()),
+                false => config_url.join(input.as_str()),
+            };
+            return Ok(ReadablePath::from_url(url.map_err(|_e| Error::InvalidUrl)?));
         }
 
         // Case file / http(s) / py
@@ -118,6 +171,12 @@ impl ReadablePath {
             if url.scheme() == "py" {
                 return py_url_to_url(url).map(ReadablePath::from_url);
             }
+            if url.scheme() == "node" {
+                return node_url_to_url(input).map(ReadablePath::from_url);
+            }
+            if url.scheme() == "cargo" {
+                return cargo_url_to_url(url).map(ReadablePath::from_url);
+            }
             return Ok(ReadablePath::from_url(url));
         }
 
@@ -129,7 +188,10 @@ impl ReadablePath {
     }
 
     pub fn join(&self, file: &str) -> ReadablePath {
-        ReadablePath::from_url(self.as_ref().join(file).unwrap())
+        match self.as_ref().scheme().starts_with("git+") {
+            true => ReadablePath::from_url(git::join(self.as_ref(), file).unwrap()),
+            false => ReadablePath::from_url(self.as_ref().join(file).unwrap()),
+        }
     }
 }
 
@@ -143,14 +205,15 @@ impl ReadPath for ReadablePath {
                     .as_ref()
                     .to_file_path()
                     .map_err(|_| PathError::UnsupportedScheme("invalid file path".into()))?;
-                std::fs::copy(&path, dest.as_ref())?;
+                copy_atomically(&path, dest.as_ref())?;
                 Ok(())
             }
             "http" | "https" => {
-                let resp = reqwest::blocking::get(self.as_ref().clone())?;
-                let bytes = resp.bytes()?;
-                let mut out = std::fs::File::create(dest.as_ref())?;
-                out.write_all(&bytes)?;
+                write_atomically(dest.as_ref(), &self.read_to_bytes()?, None)?;
+                Ok(())
+            }
+            scheme if scheme.starts_with("git+") => {
+                write_atomically(dest.as_ref(), &self.read_to_bytes()?, None)?;
                 Ok(())
             }
             other => Err(PathError::UnsupportedScheme(other.into())),
@@ -164,9 +227,10 @@ impl ReadPath for ReadablePath {
                     .to_file_path()
                     .expect("an url with a file scheme is a valid file path"),
             )?),
-            "http" | "https" => Ok(reqwest::blocking::get(self.as_ref().clone())?
-                .bytes()?
-                .into()),
+            "http" | "https" => http::fetch(self.as_ref())?
+                .ok_or_else(|| PathError::NotFound(self.as_ref().to_string())),
+            scheme if scheme.starts_with("git+") => git::read(self.as_ref())?
+                .ok_or_else(|| PathError::NotFound(self.as_ref().to_string())),
             other => Err(PathError::UnsupportedScheme(other.into())),
         }
     }
@@ -178,10 +242,8 @@ impl ReadPath for ReadablePath {
                 .to_file_path()
                 .map_err(|_| PathError::UnsupportedScheme("invalid file path".into()))?
                 .exists()),
-            "http" | "https" => {
-                let resp = reqwest::blocking::get(self.as_ref().clone())?;
-                Ok(resp.status().is_success())
-            }
+            "http" | "https" => Ok(http::fetch(self.as_ref())?.is_some()),
+            scheme if scheme.starts_with("git+") => Ok(git::read(self.as_ref())?.is_some()),
             other => Err(PathError::UnsupportedScheme(other.into())),
         }
     }
@@ -210,16 +272,14 @@ impl WritablePath {
             return Ok(WritablePath::new(PathBuf::from(input)));
         }
 
-        // case: relative to cwd
-        let cwd = std::env::current_dir()
-            .map_err(|e| e.to_string())
-            .map_err(|_| Error::InvalidUrl)?;
+        // case: relative to cwd, or to the base dir of the run
+        let cwd = base_dir().map_err(|_| Error::InvalidUrl)?;
         let full_path = cwd.join(input);
         Ok(WritablePath::new(full_path))
     }
 
     pub fn write_from_string(&self, content: &str) -> Result<(), Error> {
-        Ok(std::fs::write(self.as_ref(), content)?)
+        Ok(write_atomically(self.as_ref(), content.as_bytes(), None)?)
     }
 
     pub fn exists(&self) -> bool {
@@ -243,58 +303,326 @@ impl ReadPath for WritablePath {
     }
 
     fn copy(&self, dest: &WritablePath) -> Result<(), PathError> {
-        std::fs::copy(self.as_ref(), dest.as_ref())?;
+        let permissions = std::fs::metadata(self.as_ref())?.permissions();
+        write_atomically(
+            dest.as_ref(),
+            &std::fs::read(self.as_ref())?,
+            Some(permissions),
+        )?;
         Ok(())
     }
 }
 
+/// Follow the symlinks to the file which is actually written, so a symlink stays a symlink
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    let mut path = path.to_path_buf();
+    // limit the number of links to follow, to stop on a symlink loop
+    for _ in 0..40 {
+        match std::fs::read_link(&path) {
+            Ok(target) => {
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    path
+}
+
+/// An interrupted write never leaves a half written file behind. The mode and ownership of an
+/// existing file are kept, `permissions` are used for a new file.
+pub(crate) fn write_atomically(
+    path: &Path,
+    contents: &[u8],
+    permissions: Option<Permissions>,
+) -> std::io::Result<()> {
+    write_file_atomically(path, contents, permissions, true)
+}
+
+/// Like `fs::copy`, the destination gets the mode of the source, also when it exists.
+pub(crate) fn copy_atomically(source: &Path, dest: &Path) -> std::io::Result<()> {
+    let permissions = std::fs::metadata(source)?.permissions();
+    write_file_atomically(dest, &std::fs::read(source)?, Some(permissions), false)
+}
+
+fn write_file_atomically(
+    path: &Path,
+    contents: &[u8],
+    permissions: Option<Permissions>,
+    keep_mode: bool,
+) -> std::io::Result<()> {
+    let path = resolve_symlinks(path);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let metadata = std::fs::metadata(&path).ok();
+
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".check-config-").suffix(".tmp");
+    // a new file gets the default permissions, limited by the umask
+    #[cfg(not(target_os = "windows"))]
+    if metadata.is_none() && permissions.is_none() {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(Permissions::from_mode(0o666));
+    }
+    let mut file = builder.tempfile_in(dir)?;
+    file.write_all(contents)?;
+    file.as_file().sync_all()?;
+
+    match (&metadata, permissions) {
+        (Some(metadata), _) if keep_mode => {
+            file.as_file().set_permissions(metadata.permissions())?
+        }
+        (_, Some(permissions)) => file.as_file().set_permissions(permissions)?,
+        (_, None) => (),
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    if let Some(metadata) = &metadata {
+        use std::os::unix::fs::MetadataExt;
+
+        if let Err(e) =
+            std::os::unix::fs::fchown(file.as_file(), Some(metadata.uid()), Some(metadata.gid()))
+        {
+            // overwriting the file in place would keep the ownership, but it is not atomic
+            log::warn!(
+                "⚠ ownership of {} can not be kept ({e}), it is owned by the current user",
+                path.to_string_lossy()
+            );
+        }
+    }
+
+    file.persist(&path).map_err(|e| e.error)?;
+    Ok(())
+}
+
 #[cfg(test)]
-fn get_python_package_path(module: &str) -> Option<Url> {
-    Url::parse(format!("file:///path/to/python/lib/site-packages/{module}").as_str()).ok()
+fn get_python_package_path(module: &str) -> Result<Url, Error> {
+    Ok(Url::parse(
+        format!("file:///path/to/python/lib/site-packages/{module}").as_str(),
+    )?)
 }
 
 #[cfg(not(test))]
-fn get_python_package_path(module: &str) -> Option<Url> {
-    let output = match std::process::Command::new("python")
-        .args([
-            "-c",
-            format!("import importlib; print(importlib.import_module('{module}').__file__)")
-                .as_str(),
-        ])
-        .output()
-    {
-        Err(_) => {
-            log::error!("Python can not be called");
-            std::process::exit(1);
-        }
-        Ok(output) => output,
+fn get_python_package_path(module: &str) -> Result<Url, Error> {
+    let package_dir = python::package_dir(module)?;
+    Url::from_directory_path(package_dir).map_err(|_| Error::InvalidUrl)
+}
+
+fn py_url_to_url(package_uri: Url) -> Result<Url, Error> {
+    let Some(package_name) = package_uri.host_str() else {
+        log::error!("{package_uri} has no python package");
+        return Err(Error::NoValidPythonURL);
     };
-    let path = String::from_utf8(output.stdout)
-        .expect("Read output from Python command")
-        .trim()
-        .to_string();
-    let path = path.rsplit_once('/').unwrap().0;
 
-    Url::parse(&format!("file://{path}/")).ok()
+    let module_url = get_python_package_path(package_name).inspect_err(|e| {
+        log::error!("{package_name} is not a valid python package ({e})");
+    })?;
+    let path_inside_package_without_leading_slash = package_uri.path().trim_start_matches('/');
+
+    Ok(module_url.join(path_inside_package_without_leading_slash)?)
+}
+
+fn find_node_package(dir: &Path, package: &str, node_path: Option<&OsStr>) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join("node_modules"))
+        .chain(node_path.into_iter().flat_map(std::env::split_paths))
+        .map(|node_modules| node_modules.join(package))
+        .find(|package_dir| package_dir.is_dir())
 }
 
-fn py_url_to_url(package_uri: Url) -> Result<Url, Error> {
-    let package_name = package_uri.host().expect("host is present");
+/// The package of a scoped package is `@<scope>/<name>`, which is not a valid url host.
+fn split_node_url(input: &str) -> Option<(&str, &str)> {
+    let package_and_path = input.strip_prefix("node://")?;
+    let segments = match package_and_path.starts_with('@') {
+        true => 2,
+        false => 1,
+    };
+    let (end_of_package, _) = package_and_path.match_indices('/').nth(segments - 1)?;
+    let (package, path) = package_and_path.split_at(end_of_package);
+    Some((package, path.trim_start_matches('/')))
+}
+
+fn node_url_to_url(input: &str) -> Result<Url, Error> {
+    let Some((package, path)) = split_node_url(input) else {
+        log::error!("{input} is not a valid node url");
+        return Err(Error::NoValidNodeURL);
+    };
+    let node_path = std::env::var_os("NODE_PATH");
+    let package_dir = base_dir()
+        .ok()
+        .and_then(|cwd| find_node_package(&cwd, package, node_path.as_deref()));
+    let Some(package_dir) = package_dir else {
+        log::error!("{package} is not found in node_modules");
+        return Err(Error::NoValidNodeURL);
+    };
+
+    Url::from_directory_path(package_dir)
+        .map_err(|_| Error::InvalidUrl)?
+        .join(path)
+        .map_err(Error::from)
+}
 
-    let module_url = match get_python_package_path(package_name.to_string().as_str()) {
-        Some(url) => url,
-        None => {
-            log::error!("{package_name} is not a valid python package");
-            return Err(Error::NoValidPythonURL);
+#[derive(Debug, Clone, PartialEq)]
+struct CargoPackage {
+    id: String,
+    name: String,
+    dir: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CargoPackages {
+    packages: Vec<CargoPackage>,
+    workspace_members: HashSet<String>,
+    // ids of the packages on which the root package, or the members of a virtual workspace,
+    // depend directly
+    root_dependencies: HashSet<String>,
+}
+
+impl CargoPackages {
+    /// With several versions of the crate, the one of the workspace is taken, and else the one on
+    /// which the root package depends.
+    fn package_dir(&self, crate_name: &str) -> Result<&Path, String> {
+        // crate names are used with underscores or dashes
+        let candidates = self
+            .packages
+            .iter()
+            .filter(|package| package.name.replace('-', "_") == crate_name.replace('-', "_"))
+            .collect::<Vec<_>>();
+        let selected = match candidates.as_slice() {
+            [] => {
+                return Err(format!(
+                    "{crate_name} is not a crate of the cargo workspace"
+                ));
+            }
+            [package] => vec![*package],
+            _ => {
+                let members = candidates
+                    .iter()
+                    .filter(|package| self.workspace_members.contains(&package.id))
+                    .copied()
+                    .collect::<Vec<_>>();
+                if members.is_empty() {
+                    candidates
+                        .iter()
+                        .filter(|package| self.root_dependencies.contains(&package.id))
+                        .copied()
+                        .collect()
+                } else {
+                    members
+                }
+            }
+        };
+        match selected.as_slice() {
+            [package] => Ok(&package.dir),
+            _ => Err(format!(
+                "{crate_name} is ambiguous, the cargo workspace has the versions {}",
+                candidates
+                    .iter()
+                    .map(|package| package.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
         }
+    }
+}
+
+fn cargo_packages(metadata: &[u8]) -> Option<CargoPackages> {
+    let metadata: serde_json::Value = serde_json::from_slice(metadata).ok()?;
+    let ids = |value: &serde_json::Value| {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| id.as_str().map(str::to_string))
+            .collect::<HashSet<_>>()
     };
-    let path_inside_package_without_leading_slash = package_uri
-        .path()
-        .split_once('/')
-        .expect("valid path with a leading slash")
-        .1;
+    let packages = metadata["packages"]
+        .as_array()?
+        .iter()
+        .filter_map(|package| {
+            let manifest_path = Path::new(package["manifest_path"].as_str()?);
+            Some(CargoPackage {
+                id: package["id"].as_str()?.to_string(),
+                name: package["name"].as_str()?.to_string(),
+                dir: manifest_path.parent()?.to_path_buf(),
+            })
+        })
+        .collect();
+    let workspace_members = ids(&metadata["workspace_members"]);
+    let roots = match metadata["resolve"]["root"].as_str() {
+        Some(root) => HashSet::from([root.to_string()]),
+        None => workspace_members.clone(),
+    };
+    let root_dependencies = metadata["resolve"]["nodes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|node| node["id"].as_str().is_some_and(|id| roots.contains(id)))
+        .flat_map(|node| ids(&node["dependencies"]))
+        .collect();
+    Some(CargoPackages {
+        packages,
+        workspace_members,
+        root_dependencies,
+    })
+}
 
-    Ok(module_url.join(path_inside_package_without_leading_slash)?)
+// the packages of the cargo workspace per base dir, so `cargo metadata` is executed once per run
+// and project dir of --each
+static CARGO_PACKAGES: OnceLock<Mutex<HashMap<PathBuf, Option<CargoPackages>>>> = OnceLock::new();
+
+fn read_cargo_packages(dir: &Path) -> Option<CargoPackages> {
+    let mut command = std::process::Command::new("cargo");
+    command
+        .args(["metadata", "--format-version", "1"])
+        .current_dir(dir);
+    if http::cache_config().offline {
+        command.arg("--offline");
+    }
+    match command.output() {
+        Ok(output) if output.status.success() => cargo_packages(&output.stdout),
+        Ok(output) => {
+            log::error!(
+                "cargo metadata failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            None
+        }
+        Err(e) => {
+            log::error!("cargo can not be called ({e})");
+            None
+        }
+    }
+}
+
+fn cargo_url_to_url(crate_uri: Url) -> Result<Url, Error> {
+    let crate_name = crate_uri.host_str().unwrap_or_default();
+
+    let dir = base_dir().map_err(|_| Error::InvalidUrl)?;
+    let packages = CARGO_PACKAGES
+        .get_or_init(Default::default)
+        .lock()
+        .expect("lock is not poisoned")
+        .entry(dir)
+        .or_insert_with_key(|dir| read_cargo_packages(dir))
+        .clone();
+    let Some(packages) = packages else {
+        log::error!("{crate_name} is not a crate of the cargo workspace");
+        return Err(Error::NoValidCargoURL);
+    };
+    let package_dir = packages.package_dir(crate_name).map_err(|e| {
+        log::error!("{e}");
+        Error::NoValidCargoURL
+    })?;
+
+    Url::from_directory_path(package_dir)
+        .map_err(|_| Error::InvalidUrl)?
+        .join(crate_uri.path().trim_start_matches('/'))
+        .map_err(Error::from)
 }
 
 #[cfg(test)]
@@ -303,6 +631,175 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn test_split_node_url() {
+        assert_eq!(
+            split_node_url("node://prettier/index.js"),
+            Some(("prettier", "index.js"))
+        );
+        assert_eq!(
+            split_node_url("node://@company/config/eslint/base.json"),
+            Some(("@company/config", "eslint/base.json"))
+        );
+        assert_eq!(split_node_url("node://prettier"), None);
+        assert_eq!(split_node_url("node://@company/config"), None);
+    }
+
+    #[test]
+    fn test_find_node_package() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("project");
+        let nested = project.join("packages").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(project.join("node_modules/@company/config")).unwrap();
+        std::fs::create_dir_all(nested.join("node_modules/@company/config")).unwrap();
+        let global = dir.path().join("global");
+        std::fs::create_dir_all(global.join("prettier")).unwrap();
+
+        assert_eq!(
+            find_node_package(&nested, "@company/config", None),
+            Some(nested.join("node_modules/@company/config"))
+        );
+        assert_eq!(
+            find_node_package(&project.join("packages"), "@company/config", None),
+            Some(project.join("node_modules/@company/config"))
+        );
+        assert_eq!(find_node_package(&nested, "prettier", None), None);
+        assert_eq!(
+            find_node_package(&nested, "prettier", Some(global.as_os_str())),
+            Some(global.join("prettier"))
+        );
+    }
+
+    #[test]
+    fn test_cargo_packages() {
+        let metadata = br#"{
+            "packages": [
+                {"id": "check-config 0.9.0", "name": "check-config", "manifest_path": "/work/check-config/Cargo.toml"},
+                {"id": "serde 1.0.0", "name": "serde", "manifest_path": "/registry/serde-1.0.0/Cargo.toml"},
+                {"id": "rand 0.8.0", "name": "rand", "manifest_path": "/registry/rand-0.8.0/Cargo.toml"},
+                {"id": "rand 0.9.0", "name": "rand", "manifest_path": "/registry/rand-0.9.0/Cargo.toml"},
+                {"id": "tools 0.1.0", "name": "tools", "manifest_path": "/registry/tools-0.1.0/Cargo.toml"},
+                {"id": "tools 0.2.0", "name": "tools", "manifest_path": "/registry/tools-0.2.0/Cargo.toml"},
+                {"id": "local-config 1.0.0", "name": "local-config", "manifest_path": "/registry/local-config-1.0.0/Cargo.toml"},
+                {"id": "local-config 2.0.0", "name": "local-config", "manifest_path": "/work/check-config/local-config/Cargo.toml"}
+            ],
+            "workspace_members": ["check-config 0.9.0", "local-config 2.0.0"],
+            "resolve": {
+                "root": "check-config 0.9.0",
+                "nodes": [
+                    {"id": "check-config 0.9.0", "dependencies": ["serde 1.0.0", "rand 0.9.0", "local-config 2.0.0"]},
+                    {"id": "serde 1.0.0", "dependencies": ["rand 0.8.0", "tools 0.1.0", "tools 0.2.0"]}
+                ]
+            },
+            "workspace_root": "/work/check-config"
+        }"#;
+        let packages = cargo_packages(metadata).unwrap();
+
+        assert_eq!(
+            packages.package_dir("check_config"),
+            Ok(Path::new("/work/check-config"))
+        );
+        assert_eq!(
+            packages.package_dir("serde"),
+            Ok(Path::new("/registry/serde-1.0.0"))
+        );
+        // the version of the workspace, and else the one on which the root package depends
+        assert_eq!(
+            packages.package_dir("local-config"),
+            Ok(Path::new("/work/check-config/local-config"))
+        );
+        assert_eq!(
+            packages.package_dir("rand"),
+            Ok(Path::new("/registry/rand-0.9.0"))
+        );
+        assert_eq!(
+            packages.package_dir("tools"),
+            Err(
+                "tools is ambiguous, the cargo workspace has the versions tools 0.1.0, tools 0.2.0"
+                    .into()
+            )
+        );
+        assert_eq!(
+            packages.package_dir("unknown"),
+            Err("unknown is not a crate of the cargo workspace".into())
+        );
+        assert_eq!(cargo_packages(b"no json"), None);
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("file");
+
+        write_atomically(&file, b"new", None).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new");
+
+        write_atomically(&file, b"overwritten", None).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "overwritten");
+
+        // no temporary files are left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_write_atomically_keeps_symlink_and_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("dotfiles")).unwrap();
+        let target = dir.path().join("dotfiles").join("bashrc");
+        let link = dir.path().join(".bashrc");
+        std::fs::write(&target, "old").unwrap();
+        std::fs::set_permissions(&target, Permissions::from_mode(0o640)).unwrap();
+        std::os::unix::fs::symlink("dotfiles/bashrc", &link).unwrap();
+
+        write_atomically(&link, b"new", None).unwrap();
+
+        assert_eq!(
+            std::fs::read_link(&link).unwrap(),
+            PathBuf::from("dotfiles/bashrc")
+        );
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        assert_eq!(
+            std::fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+            0o640
+        );
+
+        // a new file gets the given permissions
+        let script = dir.path().join("script");
+        write_atomically(&script, b"", Some(Permissions::from_mode(0o755))).unwrap();
+        assert_eq!(
+            std::fs::metadata(&script).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_copy_keeps_mode_of_source() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("script");
+        let dest = dir.path().join("dest");
+        std::fs::write(&source, "new").unwrap();
+        std::fs::set_permissions(&source, Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(&dest, "old").unwrap();
+        std::fs::set_permissions(&dest, Permissions::from_mode(0o600)).unwrap();
+
+        ReadablePath::from_url(Url::from_file_path(&source).unwrap())
+            .copy(&WritablePath::new(dest.clone()))
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "new");
+        assert_eq!(
+            std::fs::metadata(&dest).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+    }
+
     #[test]
     fn test_config_readable_path() {
         let path = ReadablePath::from_string(
@@ -363,6 +860,19 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_url_is_fetched_once() {
+        use crate::http::tests::{response, serve};
+
+        let (url, server) = serve(vec![response("200 OK", "", "contents")]);
+        let path = ReadablePath::from_url(url);
+
+        assert!(path.exists().unwrap());
+        // the server accepts one connection, so a second request would fail
+        assert_eq!(path.read_to_string().unwrap(), "contents");
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_exists() {
         let dir = tempdir().unwrap();
@@ -399,6 +909,25 @@ mod test {
         assert!(WritablePath::new(tmp_path).exists());
     }
 
+    #[test]
+    fn test_with_base_dir() {
+        let dir = tempdir().unwrap();
+        let cwd = std::env::current_dir().unwrap();
+
+        let path = with_base_dir(dir.path(), || {
+            assert_eq!(base_dir().unwrap(), dir.path());
+            WritablePath::from_string("pyproject.toml").unwrap()
+        });
+
+        assert_eq!(path.as_ref(), &dir.path().join("pyproject.toml"));
+        assert_eq!(
+            WritablePath::from_string("pyproject.toml")
+                .unwrap()
+                .as_ref(),
+            &cwd.join("pyproject.toml")
+        );
+    }
+
     #[test]
     fn test_uris() {
         assert_eq!(